@@ -0,0 +1,46 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vbus_core::{Payload, ReadLimits, RecordingReader, fuzzing};
+
+#[derive(bincode::Encode, bincode::Decode)]
+struct Data {
+    values: Vec<u32>,
+    name: String,
+}
+
+impl Payload for Data {
+    fn format_name() -> &'static str {
+        "fuzz" // short, so that the fuzzer finds it
+    }
+}
+
+// Whatever the input, reading must end with an error or the end of the recording. Version 1
+// recordings are accepted, as by default.
+fuzz_target!(|input: &[u8]| {
+    let Some((&mode, bytes)) = input.split_first() else {
+        return;
+    };
+    let recovery = mode & 1 == 1;
+    let seek = (mode & 2 == 2).then_some(u64::from(mode >> 2));
+
+    fuzzing::read_stream::<Data>(bytes, recovery, seek);
+
+    let limits = ReadLimits::new()
+        .max_chunk_size(1 << 20)
+        .max_format_name(64);
+    let Ok(mut reader) =
+        RecordingReader::<Data>::from_read(std::io::Cursor::new(bytes.to_vec()), &limits)
+    else {
+        return;
+    };
+    reader.set_recovery(recovery);
+
+    for message in reader.by_ref() {
+        if message.is_err() {
+            break;
+        }
+    }
+
+    let _ = reader.losses();
+});
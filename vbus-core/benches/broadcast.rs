@@ -0,0 +1,70 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use vbus_core::{Channel, Payload, ThreadedConsumer};
+
+const MESSAGES: usize = 10_000;
+const SUBSCRIBERS: [usize; 3] = [1, 8, 64];
+
+#[derive(bincode::Encode, bincode::Decode)]
+struct Sample {
+    value: u64,
+}
+
+impl Payload for Sample {}
+
+fn push_only(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_idle_subscribers");
+    group.throughput(Throughput::Elements(MESSAGES as u64));
+
+    for n in SUBSCRIBERS {
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter_batched(
+                || {
+                    let channel = Channel::<Sample>::new();
+                    let subscriptions: Vec<_> = (0..n).map(|_| channel.subscribe()).collect();
+                    (channel, subscriptions)
+                },
+                |(channel, subscriptions)| {
+                    for value in 0..MESSAGES as u64 {
+                        channel.push(Sample { value });
+                    }
+                    (channel, subscriptions) // dropped outside of the measurement
+                },
+                criterion::BatchSize::PerIteration,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+fn push_and_consume(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_threaded_consumers");
+    group.throughput(Throughput::Elements(MESSAGES as u64));
+    group.sample_size(20);
+
+    for n in SUBSCRIBERS {
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter(|| {
+                let channel = Channel::<Sample>::new();
+                let consumers: Vec<ThreadedConsumer<Sample>> = (0..n)
+                    .map(|_| {
+                        channel.new_threaded_consumer(|messages| {
+                            std::hint::black_box(messages.iter().map(|m| m.value).sum::<u64>());
+                        })
+                    })
+                    .collect();
+
+                for value in 0..MESSAGES as u64 {
+                    channel.push(Sample { value });
+                }
+
+                drop(consumers);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, push_only, push_and_consume);
+criterion_main!(benches);
@@ -1,5 +1,6 @@
 use crate::private::queue::Queue;
-use crate::{Channel, Message, Payload};
+use crate::{Channel, Message, OverflowPolicy, Payload};
+use std::time::Duration;
 
 pub(crate) struct Consumer<T: Payload> {
     channel: Channel<T>,
@@ -8,7 +9,14 @@ pub(crate) struct Consumer<T: Payload> {
 
 impl<T: Payload> Consumer<T> {
     pub fn new(channel: &Channel<T>) -> Self {
-        let queue = Queue::<T>::new();
+        Self::with_queue(channel, Queue::<T>::new())
+    }
+
+    pub fn new_bounded(channel: &Channel<T>, capacity: usize, policy: OverflowPolicy) -> Self {
+        Self::with_queue(channel, Queue::<T>::new_bounded(capacity, policy))
+    }
+
+    fn with_queue(channel: &Channel<T>, queue: Queue<T>) -> Self {
         channel.add_queue(&queue);
 
         Self {
@@ -25,12 +33,18 @@ impl<T: Payload> Consumer<T> {
         self.queue.wait_pull()
     }
 
-    #[cfg(test)]
-    pub(crate) fn pull(&self) -> Vec<Message<T>> {
+    pub fn queue(&self) -> &Queue<T> {
+        &self.queue
+    }
+
+    pub fn wait_pull_timeout(&self, timeout: Duration) -> Vec<Message<T>> {
+        self.queue.wait_pull_timeout(timeout)
+    }
+
+    pub fn pull(&self) -> Vec<Message<T>> {
         self.queue.pull()
     }
 
-    #[cfg(test)]
     pub(crate) fn channel(&self) -> &Channel<T> {
         &self.channel
     }
@@ -38,6 +52,7 @@ impl<T: Payload> Consumer<T> {
 
 impl<T: Payload> Drop for Consumer<T> {
     fn drop(&mut self) {
+        self.queue.close(); // a pusher may be blocked on us while holding the channel
         self.channel.remove_queue(&self.queue);
     }
 }
@@ -46,7 +61,7 @@ impl<T: Payload> Drop for Consumer<T> {
 mod tests {
     use crate::private::Consumer;
     use crate::private::test_tools::TestPayload;
-    use crate::{Channel, Message};
+    use crate::{Channel, Message, OverflowPolicy};
     use std::thread::sleep;
     use std::time::{Duration, Instant};
 
@@ -83,6 +98,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_drop_blocking_consumer() {
+        let channel = Channel::<TestPayload>::new();
+        let consumer = channel.new_bounded_consumer(1, OverflowPolicy::Block);
+
+        channel.push(TestPayload::new(0));
+
+        let channel_for_thread = channel.clone();
+        let join_handle = std::thread::spawn(move || {
+            channel_for_thread.push(TestPayload::new(1));
+        });
+
+        sleep(Duration::from_millis(100));
+        drop(consumer);
+        join_handle.join().unwrap();
+
+        assert_eq!(channel.queues_len(), 0);
+    }
+
     #[test]
     fn test_consumer_api() {
         let channel = Channel::<TestPayload>::new();
@@ -91,31 +125,25 @@ mod tests {
         assert_eq!(consumer.queue.len(), 0);
         assert_eq!(consumer.pull().len(), 0);
 
-        consumer
-            .queue
-            .push(Message::new(Instant::now(), TestPayload::default()));
+        channel.push_message(Message::new(Instant::now(), TestPayload::default()));
         assert_eq!(consumer.queue.len(), 1);
-        consumer
-            .queue
-            .push(Message::new(Instant::now(), TestPayload::default()));
+        channel.push_message(Message::new(Instant::now(), TestPayload::default()));
         assert_eq!(consumer.queue.len(), 2);
         assert_eq!(consumer.pull().len(), 2);
         assert_eq!(consumer.queue.len(), 0);
 
-        consumer
-            .queue
-            .push(Message::new(Instant::now(), TestPayload::default()));
+        channel.push_message(Message::new(Instant::now(), TestPayload::default()));
         assert_eq!(consumer.queue.len(), 1);
         assert_eq!(consumer.wait_pull().len(), 1);
         assert_eq!(consumer.queue.len(), 0);
 
         let wait_time = Duration::from_millis(200);
-        let queue_for_thread = consumer.queue.clone();
+        let channel_for_thread = channel.clone();
         let msg = Message::new(Instant::now(), TestPayload::default());
         let now = Instant::now();
         let _ = std::thread::spawn(move || {
             sleep(wait_time);
-            queue_for_thread.push(msg)
+            channel_for_thread.push_message(msg)
         });
         assert_eq!(consumer.wait_pull().len(), 1);
         let elapsed = now.elapsed();
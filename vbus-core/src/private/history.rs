@@ -0,0 +1,106 @@
+use crate::private::ring::{Ring, RingCursor};
+use crate::{Latch, Payload};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+// Keeps the channel ring alive from the oldest latched message onwards
+pub(crate) struct History<T: Payload> {
+    latch: Latch,
+    start: RingCursor<T>,
+    push_times: VecDeque<Instant>, // one per message after start
+}
+
+impl<T: Payload> History<T> {
+    pub fn new(latch: Latch, ring: &Ring<T>) -> Self {
+        Self {
+            latch,
+            start: ring.cursor(),
+            push_times: VecDeque::new(),
+        }
+    }
+
+    // To be called after each message is written to the ring
+    pub fn record(&mut self) {
+        let now = Instant::now();
+        self.push_times.push_back(now);
+        self.prune(now);
+    }
+
+    pub fn replay(&mut self) -> RingCursor<T> {
+        self.prune(Instant::now());
+        self.start.clone()
+    }
+
+    fn prune(&mut self, now: Instant) {
+        let keep = match self.latch {
+            Latch::Count(n) => n,
+            Latch::Duration(d) => self
+                .push_times
+                .iter()
+                .rev()
+                .take_while(|pushed| now.duration_since(**pushed) <= d)
+                .count(),
+        };
+
+        while self.push_times.len() > keep {
+            self.push_times.pop_front();
+            self.start.next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Message;
+    use crate::private::test_tools::TestPayload;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    fn values(history: &mut History<TestPayload>) -> Vec<usize> {
+        let mut cursor = history.replay();
+        std::iter::from_fn(|| cursor.next())
+            .map(|m| m.get_payload().value())
+            .collect()
+    }
+
+    fn record(ring: &Ring<TestPayload>, history: &mut History<TestPayload>, value: usize) {
+        ring.push(Message::new(Instant::now(), TestPayload::new(value)));
+        history.record();
+    }
+
+    #[test]
+    fn test_count() {
+        let ring = Ring::new();
+        let mut history = History::new(Latch::Count(3), &ring);
+        assert!(values(&mut history).is_empty());
+
+        for i in 0..5 {
+            record(&ring, &mut history, i);
+        }
+        assert_eq!(values(&mut history), [2, 3, 4]);
+    }
+
+    #[test]
+    fn test_count_zero() {
+        let ring = Ring::new();
+        let mut history = History::new(Latch::Count(0), &ring);
+        record(&ring, &mut history, 0);
+        assert!(values(&mut history).is_empty());
+    }
+
+    #[test]
+    fn test_duration() {
+        const LATCH_TIME: Duration = Duration::from_millis(200);
+
+        let ring = Ring::new();
+        let mut history = History::new(Latch::Duration(LATCH_TIME), &ring);
+        record(&ring, &mut history, 0);
+        sleep(LATCH_TIME + Duration::from_millis(50));
+        record(&ring, &mut history, 1);
+        assert_eq!(values(&mut history), [1]);
+
+        sleep(LATCH_TIME + Duration::from_millis(50));
+        assert!(values(&mut history).is_empty());
+    }
+}
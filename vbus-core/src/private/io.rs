@@ -1,29 +1,185 @@
-use crate::{Error, Message, Payload};
-use std::io::{Read, Write};
-use std::mem::MaybeUninit;
-use std::time::Instant;
+use crate::{Error, Message, Payload, ReadLimits, RecordingLoss};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::time::{Duration, Instant, SystemTime};
+
+/*
+ * Recording layout, version 2. All integers are little-endian.
+ *
+ *   stream header   magic "VBUS" (4 bytes) | version: u16 | anchor: i64
+ *   chunk header    marker (4 bytes) | index: u64 | size: u64 | offset: i64 | crc: u32 |
+ *                   header crc: u32
+ *   chunk data      size bytes
+ *   index entry     chunk: u64 | offset: i64 | position: u64
+ *   trailer         position: u64 | "VBIX"
+ *
+ * The first chunk holds the payload format name (UTF-8), each following one a bincode-encoded
+ * payload. Chunks are numbered from 0. The crc is the CRC-32 of the data, the header crc that of
+ * the header bytes before it: a reader that lost track can look for the marker and check the
+ * header crc to find the next chunk.
+ *
+ * The anchor is the wall-clock time at which the stream was created, in nanoseconds since the
+ * Unix epoch. Offsets are the message time stamps, in nanoseconds relative to that same moment:
+ * they come from the monotonic clock, and are negative for messages older than the stream.
+ *
+ * A stream closed properly ends with an index chunk, marked differently, whose data are index
+ * entries for one message chunk out of INDEX_INTERVAL, giving their offset and their position in
+ * bytes from the start of the stream. The trailer gives the position of the index chunk.
+ *
+ * Version 1 wrote StreamHeader and ChunkHeaderV1 as raw memory, in host order, as laid out on a
+ * 64-bit host:
+ *
+ *   chunk header    size: usize | time stamp, an Instant: secs: i64 | nanos: u32 | padding (4 bytes)
+ *
+ * It is still read, but its time stamps only make sense on the machine and boot that wrote it.
+ */
 
 const MAGIC: [u8; 4] = [b'V', b'B', b'U', b'S'];
-const CURRENT_VERSION: u16 = 1;
+const CURRENT_VERSION: u16 = 2;
+const VERSION_1: u16 = 1;
+const CHUNK_MARKER: [u8; 4] = [0xc3, 0x5a, 0x96, 0x0f]; // arbitrary, not valid UTF-8
+const INDEX_MARKER: [u8; 4] = [0xc3, 0x5a, 0x96, 0x1f];
+const TRAILER_MAGIC: [u8; 4] = [b'V', b'B', b'I', b'X'];
+const TRAILER_SIZE: usize = 12;
+const INDEX_INTERVAL: u64 = 64;
 
 struct ChunkHeader {
-    size: usize,
-    time_stamp: Instant,
+    marker: [u8; 4],
+    index: u64,
+    size: u64,
+    offset: i64,
+    crc: u32,
 }
 
 impl ChunkHeader {
-    fn new(size: usize, time_stamp: Instant) -> Self {
-        Self { size, time_stamp }
+    const SIZE: usize = 36;
+
+    fn encode(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..4].copy_from_slice(&self.marker);
+        bytes[4..12].copy_from_slice(&self.index.to_le_bytes());
+        bytes[12..20].copy_from_slice(&self.size.to_le_bytes());
+        bytes[20..28].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[28..32].copy_from_slice(&self.crc.to_le_bytes());
+        let header_crc = crc32fast::hash(&bytes[..32]);
+        bytes[32..].copy_from_slice(&header_crc.to_le_bytes());
+        bytes
+    }
+
+    // None if the bytes are not a valid header
+    fn decode(bytes: &[u8; Self::SIZE]) -> Option<Self> {
+        let marker: [u8; 4] = bytes[..4].try_into().unwrap();
+        let header_crc = u32::from_le_bytes(bytes[32..].try_into().unwrap());
+        if (marker != CHUNK_MARKER && marker != INDEX_MARKER)
+            || crc32fast::hash(&bytes[..32]) != header_crc
+        {
+            return None;
+        }
+
+        Some(Self {
+            marker,
+            index: u64::from_le_bytes(bytes[4..12].try_into().unwrap()),
+            size: u64::from_le_bytes(bytes[12..20].try_into().unwrap()),
+            offset: i64::from_le_bytes(bytes[20..28].try_into().unwrap()),
+            crc: u32::from_le_bytes(bytes[28..32].try_into().unwrap()),
+        })
+    }
+}
+
+struct IndexEntry {
+    chunk: u64,
+    offset: i64,
+    position: u64,
+}
+
+impl IndexEntry {
+    const SIZE: usize = 24;
+
+    fn encode(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..8].copy_from_slice(&self.chunk.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[16..].copy_from_slice(&self.position.to_le_bytes());
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        Self {
+            chunk: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            offset: i64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            position: u64::from_le_bytes(bytes[16..].try_into().unwrap()),
+        }
+    }
+}
+
+// Version 1, decoded from raw memory
+struct ChunkHeaderV1 {
+    size: u64,
+    time_stamp: i128, // nanoseconds, on the monotonic clock
+}
+
+impl ChunkHeaderV1 {
+    const SIZE: usize = 24;
+
+    #[cfg(test)]
+    fn encode(&self) -> [u8; Self::SIZE] {
+        let secs = self.time_stamp.div_euclid(1_000_000_000) as i64;
+        let nanos = self.time_stamp.rem_euclid(1_000_000_000) as u32;
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..8].copy_from_slice(&self.size.to_ne_bytes());
+        bytes[8..16].copy_from_slice(&secs.to_ne_bytes());
+        bytes[16..20].copy_from_slice(&nanos.to_ne_bytes());
+        bytes
+    }
+
+    // None if the time stamp isn't a valid Instant
+    fn decode(bytes: &[u8; Self::SIZE]) -> Option<Self> {
+        let size = u64::from_ne_bytes(bytes[..8].try_into().unwrap());
+        let secs = i64::from_ne_bytes(bytes[8..16].try_into().unwrap());
+        let nanos = u32::from_ne_bytes(bytes[16..20].try_into().unwrap());
+
+        if nanos >= 1_000_000_000 {
+            return None;
+        }
+
+        Some(Self {
+            size,
+            time_stamp: secs as i128 * 1_000_000_000 + nanos as i128,
+        })
     }
 }
 
-struct ChunkData(Vec<u8>, Instant);
+struct ChunkData(Vec<u8>, i64); // offset
 
 struct StreamHeader {
     magic: [u8; 4],
     version: u16,
 }
 
+impl StreamHeader {
+    const SIZE: usize = 6;
+
+    fn encode(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..4].copy_from_slice(&self.magic);
+        bytes[4..].copy_from_slice(&self.version.to_le_bytes());
+        bytes
+    }
+
+    fn decode(bytes: &[u8; Self::SIZE]) -> Self {
+        let magic = bytes[..4].try_into().unwrap();
+        let version = u16::from_le_bytes(bytes[4..].try_into().unwrap());
+
+        // Version 1 was written in host order
+        let version = match u16::from_ne_bytes(bytes[4..].try_into().unwrap()) {
+            VERSION_1 => VERSION_1,
+            _ => version,
+        };
+
+        Self { magic, version }
+    }
+}
+
 impl Default for StreamHeader {
     fn default() -> Self {
         Self {
@@ -34,94 +190,376 @@ impl Default for StreamHeader {
 }
 
 pub(crate) struct OutputStream<T: Payload> {
-    write: Box<dyn Write + Send>,
+    raw: RawOutputStream,
     _phantom: std::marker::PhantomData<T>,
 }
 
 impl<T: Payload> OutputStream<T> {
     pub fn new(write: Box<dyn Write + Send>) -> Result<Self, Error> {
+        Ok(Self {
+            raw: RawOutputStream::new(write, T::format_name())?,
+            _phantom: Default::default(),
+        })
+    }
+
+    pub fn append(&mut self, message: &Message<T>) -> Result<(), Error> {
+        let encoded = bincode::encode_to_vec(message.get_payload(), bincode::config::standard())?;
+        self.raw.append(encoded, message.get_type_stamp())
+    }
+}
+
+// Same stream, for payloads that are already encoded. The index is written when dropped.
+pub(crate) struct RawOutputStream {
+    write: Box<dyn Write + Send>,
+    origin: Instant, // the anchor, on the monotonic clock
+    index: u64,      // of the next chunk
+    position: u64,   // bytes written
+    entries: Vec<IndexEntry>,
+}
+
+impl RawOutputStream {
+    pub fn new(write: Box<dyn Write + Send>, format_name: &str) -> Result<Self, Error> {
+        let (anchor, origin) = (SystemTime::now(), Instant::now());
         let mut stream = Self {
             write,
-            _phantom: Default::default(),
+            origin,
+            index: 0,
+            position: 0,
+            entries: Vec::new(),
         };
 
-        stream.write_any(&StreamHeader::default())?;
-        stream.append_string(T::format_name())?;
+        stream.write_bytes(&StreamHeader::default().encode())?;
+        stream.write_bytes(&system_time_to_nanos(anchor).to_le_bytes())?;
+        stream.append_bytes(ChunkData(format_name.as_bytes().to_vec(), 0))?;
 
         Ok(stream)
     }
 
-    pub fn append(&mut self, message: &Message<T>) -> Result<(), Error> {
-        let encoded = bincode::encode_to_vec(message.get_payload(), bincode::config::standard())?;
-        let chunk_data = ChunkData(encoded, message.get_type_stamp());
-        self.append_bytes(chunk_data)?;
-        Ok(())
-    }
-
-    fn append_string(&mut self, string: &str) -> Result<(), Error> {
-        let chunk_data = ChunkData(string.as_bytes().to_vec(), Instant::now());
-        self.append_bytes(chunk_data)
+    pub fn append(&mut self, encoded: Vec<u8>, time_stamp: Instant) -> Result<(), Error> {
+        let offset = instant_to_offset(self.origin, time_stamp);
+        self.append_bytes(ChunkData(encoded, offset))
     }
 
     fn append_bytes(&mut self, chunk_data: ChunkData) -> Result<(), Error> {
-        let header = ChunkHeader::new(chunk_data.0.len(), chunk_data.1);
-        self.write_any(&header)?;
+        if self.index > 0 && (self.index - 1).is_multiple_of(INDEX_INTERVAL) {
+            self.entries.push(IndexEntry {
+                chunk: self.index,
+                offset: chunk_data.1,
+                position: self.position,
+            });
+        }
+
+        let header = ChunkHeader {
+            marker: CHUNK_MARKER,
+            index: self.index,
+            size: chunk_data.0.len() as u64,
+            offset: chunk_data.1,
+            crc: crc32fast::hash(&chunk_data.0),
+        };
+        self.write_bytes(&header.encode())?;
         self.write_bytes(chunk_data.0.as_slice())?;
+        self.index += 1;
         Ok(())
     }
 
-    fn write_any<U: Sized>(&mut self, data: &U) -> Result<(), Error> {
-        let bytes = unsafe { any_as_u8_slice(data) };
-        self.write_bytes(bytes)?;
+    fn write_index(&mut self) -> Result<(), Error> {
+        let position = self.position;
+        let data = self
+            .entries
+            .iter()
+            .flat_map(|entry| entry.encode())
+            .collect::<Vec<_>>();
+        let header = ChunkHeader {
+            marker: INDEX_MARKER,
+            index: self.index,
+            size: data.len() as u64,
+            offset: 0,
+            crc: crc32fast::hash(&data),
+        };
+
+        self.write_bytes(&header.encode())?;
+        self.write_bytes(&data)?;
+        self.write_bytes(&position.to_le_bytes())?;
+        self.write_bytes(&TRAILER_MAGIC)?;
+        self.write.flush()?;
         Ok(())
     }
 
     fn write_bytes(&mut self, data: &[u8]) -> Result<(), Error> {
         self.write.write_all(data)?;
+        self.position += data.len() as u64;
         Ok(())
     }
 }
 
+impl Drop for RawOutputStream {
+    fn drop(&mut self) {
+        let _ = self.write_index(); // readers can do without
+    }
+}
+
 pub(crate) struct InputStream<T: Payload> {
-    read: Box<dyn Read + Send>,
+    source: Source,
+    version: u16,
+    origin: Instant, // the anchor, in this process
+    anchor: SystemTime,
+    origin_v1: i128, // the anchor on the monotonic clock, for version 1
+    index: u64,      // of the next chunk
+    recovery: bool,
+    losses: Vec<RecordingLoss>,
+    limits: ReadLimits,
+    first_position: u64,                 // of the first message chunk
+    time_index: Option<Vec<IndexEntry>>, // loaded on the first seek
     _phantom: std::marker::PhantomData<T>,
 }
 
 impl<T: Payload> InputStream<T> {
     pub fn new(read: Box<dyn Read + Send>) -> Result<Self, Error> {
+        Self::new_with(read, &ReadLimits::default())
+    }
+
+    pub fn new_with(read: Box<dyn Read + Send>, limits: &ReadLimits) -> Result<Self, Error> {
+        Self::open(Input::Stream(read), limits)
+    }
+
+    // Only seekable streams can use seek_to_offset() and seek_to_message()
+    pub fn new_seekable(read: Box<dyn ReadSeek>, limits: &ReadLimits) -> Result<Self, Error> {
+        Self::open(Input::Seekable(read), limits)
+    }
+
+    fn open(input: Input, limits: &ReadLimits) -> Result<Self, Error> {
         let mut stream = Self {
-            read,
+            source: Source {
+                input,
+                unread: Vec::new(),
+                position: 0,
+            },
+            version: CURRENT_VERSION,
+            origin: Instant::now(),
+            anchor: SystemTime::now(),
+            origin_v1: 0,
+            index: 0,
+            recovery: false,
+            losses: Vec::new(),
+            limits: limits.clone(),
+            first_position: 0,
+            time_index: None,
             _phantom: Default::default(),
         };
 
-        stream.get_and_check_header()?;
-        let format = stream.get_string()?;
+        stream.version = stream.get_and_check_header()?;
+        let format = match stream.version {
+            VERSION_1 => stream.get_string_v1()?,
+            _ => {
+                let mut bytes = [0u8; 8];
+                stream.source.read_exact(&mut bytes)?;
+                stream.anchor = nanos_to_system_time(i64::from_le_bytes(bytes));
+                stream.get_string()?
+            }
+        };
 
         if format != T::format_name() {
             return Err(Error::BadFormat(format));
         }
 
+        stream.first_position = stream.source.position;
         Ok(stream)
     }
 
     pub fn get(&mut self) -> Result<Message<T>, Error> {
+        Ok(self.get_with_offset()?.0)
+    }
+
+    // Also returns the offset of the message from the anchor, in nanoseconds
+    pub fn get_with_offset(&mut self) -> Result<(Message<T>, i64), Error> {
         let chunk_data = self.read_chunk()?;
-        let (decoded, _): (T, usize) = bincode::decode_from_slice(chunk_data.0.as_slice(), bincode::config::standard())?;
-        Ok(Message::new(chunk_data.1, decoded))
+        let decoded = decode_payload(&chunk_data.0, self.limits.max_chunk_size)?;
+        let time_stamp = offset_to_instant(self.origin, chunk_data.1);
+        Ok((Message::new(time_stamp, decoded), chunk_data.1))
+    }
+
+    // Wall-clock time at which the stream was created
+    pub fn anchor(&self) -> SystemTime {
+        self.anchor
     }
 
-    fn get_and_check_header(&mut self) -> Result<(), Error> {
-        let header = self.read_stream_header()?;
+    // Skip damaged chunks rather than failing with Error::Corrupted, and the truncated end of the
+    // stream rather than failing with an I/O error. Version 1 streams can't be checked.
+    pub fn set_recovery(&mut self, recovery: bool) {
+        self.recovery = recovery;
+    }
+
+    pub fn losses(&self) -> &[RecordingLoss] {
+        &self.losses
+    }
+
+    // Positions the stream on the first message stamped at or after offset, assuming that time
+    // stamps don't go back
+    pub fn seek_to_offset(&mut self, offset: i64) -> Result<(), Error> {
+        self.seek(
+            |entry| entry.offset < offset,
+            |header| header.offset >= offset,
+        )
+    }
+
+    // Positions the stream on message n, counted from 0, or at the end
+    pub fn seek_to_message(&mut self, n: u64) -> Result<(), Error> {
+        let chunk = n.saturating_add(1);
+        self.seek(|entry| entry.chunk <= chunk, |header| header.index >= chunk)
+    }
+
+    // Jumps to the last index entry before the target, then walks the chunk headers
+    fn seek(
+        &mut self,
+        before: impl Fn(&IndexEntry) -> bool,
+        found: impl Fn(&ChunkHeader) -> bool,
+    ) -> Result<(), Error> {
+        if self.version == VERSION_1 || !self.source.is_seekable() {
+            return Err(Error::NotSeekable);
+        }
+
+        let entries = match self.time_index.take() {
+            Some(entries) => entries,
+            None => match self.load_index() {
+                Ok(entries) => entries,
+                Err(_) => self.rebuild_index()?,
+            },
+        };
+
+        let (chunk, position) = match entries.partition_point(before) {
+            0 => (1, self.first_position),
+            i => (entries[i - 1].chunk, entries[i - 1].position),
+        };
+        self.time_index = Some(entries);
+        self.index = chunk;
+        self.source.seek_to(position)?;
+
+        loop {
+            let start = self.source.position;
+            let mut bytes = [0u8; ChunkHeader::SIZE];
+
+            // The end, or a truncated chunk left to get() to report
+            if self.source.read_exact(&mut bytes).is_err() {
+                return self.source.seek_to(start);
+            }
+
+            let header = match ChunkHeader::decode(&bytes) {
+                Some(header) if header.index == self.index => header,
+                _ => return Err(Error::Corrupted { position: start }),
+            };
+
+            if header.marker == INDEX_MARKER || found(&header) {
+                return self.source.seek_to(start);
+            }
+
+            self.skip_chunk_data(start, header.size)?;
+            self.index += 1;
+        }
+    }
+
+    fn load_index(&mut self) -> Result<Vec<IndexEntry>, Error> {
+        let trailer_position = self.source.seek(SeekFrom::End(-(TRAILER_SIZE as i64)))?;
+        let mut trailer = [0u8; TRAILER_SIZE];
+        self.source.read_exact(&mut trailer)?;
+
+        if trailer[8..] != TRAILER_MAGIC {
+            return Err(Error::Corrupted {
+                position: trailer_position,
+            });
+        }
+
+        let position = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+        self.source.seek_to(position)?;
+
+        let mut bytes = [0u8; ChunkHeader::SIZE];
+        self.source.read_exact(&mut bytes)?;
+        let header = match ChunkHeader::decode(&bytes) {
+            Some(header)
+                if header.marker == INDEX_MARKER
+                    && header.size.is_multiple_of(IndexEntry::SIZE as u64) =>
+            {
+                header
+            }
+            _ => return Err(Error::Corrupted { position }),
+        };
+
+        self.check_size(header.size, false)?;
+        let chunk_data = self.read_chunk_data(header.size, 0)?;
+        if crc32fast::hash(&chunk_data.0) != header.crc {
+            return Err(Error::Corrupted { position });
+        }
+
+        // Entries point at message chunks, between the format name and the index
+        let entries = chunk_data
+            .0
+            .chunks_exact(IndexEntry::SIZE)
+            .map(IndexEntry::decode)
+            .collect::<Vec<_>>();
+        let valid = |entry: &IndexEntry| {
+            entry.chunk >= 1 && (self.first_position..position).contains(&entry.position)
+        };
+        if !entries.iter().all(valid) {
+            return Err(Error::Corrupted { position });
+        }
+
+        Ok(entries)
+    }
+
+    // Skips the data of the chunk whose header starts at start, without reading it
+    fn skip_chunk_data(&mut self, start: u64, size: u64) -> Result<(), Error> {
+        self.check_size(size, false)?;
+        match self.source.position.checked_add(size) {
+            Some(end) => self.source.seek_to(end),
+            None => Err(Error::Corrupted { position: start }),
+        }
+    }
+
+    // Without an index, e.g. after a crash, the chunk headers are read up to the first damaged one
+    fn rebuild_index(&mut self) -> Result<Vec<IndexEntry>, Error> {
+        let mut entries = Vec::new();
+        let mut chunk = 1;
+        self.source.seek_to(self.first_position)?;
+
+        loop {
+            let position = self.source.position;
+            let mut bytes = [0u8; ChunkHeader::SIZE];
+            if self.source.read_exact(&mut bytes).is_err() {
+                return Ok(entries);
+            }
+
+            let header = match ChunkHeader::decode(&bytes) {
+                Some(header) if header.marker == CHUNK_MARKER && header.index == chunk => header,
+                _ => return Ok(entries),
+            };
+
+            if (chunk - 1).is_multiple_of(INDEX_INTERVAL) {
+                entries.push(IndexEntry {
+                    chunk,
+                    offset: header.offset,
+                    position,
+                });
+            }
+
+            self.skip_chunk_data(position, header.size)?;
+            chunk += 1;
+        }
+    }
+
+    fn get_and_check_header(&mut self) -> Result<u16, Error> {
+        let mut bytes = [0u8; StreamHeader::SIZE];
+        self.source.read_exact(&mut bytes)?;
+        let header = StreamHeader::decode(&bytes);
 
         if !header.magic.eq(&MAGIC) {
             return Err(Error::BadHeader);
         }
 
-        if !header.version.eq(&CURRENT_VERSION) {
+        let version_1 = header.version == VERSION_1 && self.limits.version_1;
+        if header.version != CURRENT_VERSION && !version_1 {
             return Err(Error::BadVersion(header.version));
         }
 
-        Ok(())
+        Ok(header.version)
     }
 
     fn get_string(&mut self) -> Result<String, Error> {
@@ -129,68 +567,296 @@ impl<T: Payload> InputStream<T> {
         Ok(String::from_utf8_lossy(chunk_data.0.as_slice()).to_string())
     }
 
-    fn read_stream_header(&mut self) -> Result<StreamHeader, Error> {
-        let mut buffer = MaybeUninit::<StreamHeader>::uninit();
-        let slice = unsafe { any_as_u8_mut_slice(&mut buffer) };
-        self.read.read_exact(slice)?;
-        unsafe { Ok(buffer.assume_init()) }
+    // Version 1 has no anchor: its format name chunk was stamped when the stream was created, and
+    // raw instants only make sense in the same boot, where the wall clock can be estimated
+    fn get_string_v1(&mut self) -> Result<String, Error> {
+        let header = self.read_chunk_header_v1()?;
+        let age = nanos_to_offset(monotonic_nanos() - header.time_stamp);
+        self.origin_v1 = header.time_stamp;
+        self.origin = offset_to_instant(Instant::now(), -age);
+        self.anchor = offset_to_system_time(SystemTime::now(), -age);
+
+        self.check_size(header.size, true)?;
+        let chunk_data = self.read_chunk_data(header.size, 0)?;
+        Ok(String::from_utf8_lossy(chunk_data.0.as_slice()).to_string())
     }
 
     fn read_chunk(&mut self) -> Result<ChunkData, Error> {
-        let header = &self.read_chunk_header()?;
-        self.read_chunk_data(header)
-    }
+        if self.version == VERSION_1 {
+            let header = self.read_chunk_header_v1()?;
+            let offset = nanos_to_offset(header.time_stamp - self.origin_v1);
+            self.check_size(header.size, false)?;
+            return self.read_chunk_data(header.size, offset);
+        }
 
-    fn read_chunk_header(&mut self) -> Result<ChunkHeader, Error> {
-        let mut buffer = MaybeUninit::<ChunkHeader>::uninit();
+        let mut lost_from = None; // start of the damaged bytes being skipped
 
-        // Handle regular Eof (= 0 byte read)
-        let size = match self.read.read(unsafe { any_as_u8_mut_slice(&mut buffer) }) {
-            Ok(x) => {
-                if x == 0 {
-                    return Err(Error::RegularEof);
+        loop {
+            let start = self.source.position;
+            let mut bytes = [0u8; ChunkHeader::SIZE];
+
+            if let Err(e) = self.read_or_eof(&mut bytes) {
+                return Err(self.end_of_stream(e, lost_from.unwrap_or(start)));
+            }
+
+            // In recovery, look for a header one byte further
+            let header = match ChunkHeader::decode(&bytes) {
+                Some(header) if header.index == self.index => header,
+                Some(header) if self.recovery && header.index > self.index => header,
+                _ if !self.recovery => return Err(Error::Corrupted { position: start }),
+                _ => {
+                    self.source.unread(&bytes[1..]);
+                    lost_from.get_or_insert(start);
+                    continue;
+                }
+            };
+
+            if let Some(lost_from) = lost_from.take() {
+                self.lose(lost_from..start, header.index);
+            } else if header.index > self.index {
+                self.lose(start..start, header.index); // chunks cleanly cut out
+            }
+
+            if header.marker == INDEX_MARKER {
+                self.source.unread(&bytes); // stays the end of the stream
+                return Err(Error::RegularEof);
+            }
+
+            self.check_size(header.size, header.index == 0)?;
+            let chunk_data = match self.read_chunk_data(header.size, header.offset) {
+                Ok(chunk_data) => chunk_data,
+                Err(e) => return Err(self.end_of_stream(e, start)),
+            };
+
+            if crc32fast::hash(&chunk_data.0) != header.crc {
+                if !self.recovery {
+                    return Err(Error::Corrupted { position: start });
                 }
-                x
+
+                self.lose(start..self.source.position, header.index + 1);
+                continue;
             }
+
+            self.index += 1;
+            return Ok(chunk_data);
+        }
+    }
+
+    // In recovery, reaching the end of the stream in the middle of a chunk or of damaged bytes
+    // loses them, and ends the stream normally
+    fn end_of_stream(&mut self, error: Error, lost_from: u64) -> Error {
+        let eof = match &error {
+            Error::RegularEof => true,
+            Error::StdIo(e) => e.kind() == ErrorKind::UnexpectedEof,
+            _ => false,
+        };
+
+        if !self.recovery || !eof || lost_from == self.source.position {
+            return error;
+        }
+
+        self.lose(lost_from..self.source.position, self.index + 1);
+        Error::RegularEof
+    }
+
+    fn lose(&mut self, bytes: Range<u64>, next_index: u64) {
+        self.losses.push(RecordingLoss {
+            bytes,
+            chunks: self.index..next_index,
+        });
+        self.index = next_index;
+    }
+
+    fn read_chunk_header_v1(&mut self) -> Result<ChunkHeaderV1, Error> {
+        let start = self.source.position;
+        let mut bytes = [0u8; ChunkHeaderV1::SIZE];
+        self.read_or_eof(&mut bytes)?;
+        ChunkHeaderV1::decode(&bytes).ok_or(Error::Corrupted { position: start })
+    }
+
+    // Fails with RegularEof if the stream ends before the first byte
+    fn read_or_eof(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
+        let size = match self.source.read(buffer) {
+            Ok(0) => return Err(Error::RegularEof),
+            Ok(x) => x,
             Err(e) => return Err(Error::StdIo(e)),
         };
 
         // Get remaining bytes (if missing)
-        if size < size_of::<ChunkHeader>() {
-            let (_, slice) = unsafe { any_as_u8_mut_slice(&mut buffer) }.split_at_mut(size);
-            self.read.read_exact(slice)?;
-        }
+        self.source.read_exact(&mut buffer[size..])?;
+        Ok(())
+    }
 
-        unsafe { Ok(buffer.assume_init()) }
+    fn check_size(&self, size: u64, format_name: bool) -> Result<(), Error> {
+        let limit = match format_name {
+            true => self.limits.max_format_name,
+            false => self.limits.max_chunk_size,
+        };
+
+        match (size <= limit, format_name) {
+            (true, _) => Ok(()),
+            (false, true) => Err(Error::FormatNameTooLong { size, limit }),
+            (false, false) => Err(Error::ChunkTooLarge { size, limit }),
+        }
     }
 
-    fn read_chunk_data(&mut self, header: &ChunkHeader) -> Result<ChunkData, Error> {
-        let mut buffer = Vec::with_capacity(header.size);
+    fn read_chunk_data(&mut self, size: u64, offset: i64) -> Result<ChunkData, Error> {
+        // The buffer grows with what is actually read: a truncated stream can't make us allocate
+        // the announced size up front
+        let mut buffer = Vec::new();
+        (&mut self.source).take(size).read_to_end(&mut buffer)?;
 
-        // Avoid buffer initialization
-        unsafe {
-            buffer.spare_capacity_mut(); // not required by the compiler, but keeps Clippy quiet
-            buffer.set_len(header.size);
+        if buffer.len() as u64 != size {
+            return Err(Error::StdIo(ErrorKind::UnexpectedEof.into()));
         }
 
-        self.read.read_exact(buffer.as_mut_slice())?;
+        Ok(ChunkData(buffer, offset))
+    }
+}
+
+pub(crate) trait ReadSeek: Read + Seek + Send {}
+
+impl<R: Read + Seek + Send> ReadSeek for R {}
+
+enum Input {
+    Stream(Box<dyn Read + Send>),
+    Seekable(Box<dyn ReadSeek>),
+}
+
+// Keeps track of the position in the stream, and can be given bytes back to look for a chunk
+struct Source {
+    input: Input,
+    unread: Vec<u8>, // reversed
+    position: u64,
+}
+
+impl Source {
+    fn unread(&mut self, bytes: &[u8]) {
+        self.unread.extend(bytes.iter().rev());
+        self.position -= bytes.len() as u64;
+    }
+
+    fn is_seekable(&self) -> bool {
+        matches!(self.input, Input::Seekable(_))
+    }
+
+    fn seek(&mut self, from: SeekFrom) -> Result<u64, Error> {
+        let Input::Seekable(input) = &mut self.input else {
+            return Err(Error::NotSeekable);
+        };
+
+        self.position = input.seek(from)?;
+        self.unread.clear();
+        Ok(self.position)
+    }
+
+    fn seek_to(&mut self, position: u64) -> Result<(), Error> {
+        self.seek(SeekFrom::Start(position))?;
+        Ok(())
+    }
+}
+
+impl Read for Source {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let size = match (self.unread.is_empty(), &mut self.input) {
+            (true, Input::Stream(input)) => input.read(buffer)?,
+            (true, Input::Seekable(input)) => input.read(buffer)?,
+            (false, _) => {
+                let size = buffer.len().min(self.unread.len());
+                buffer[..size]
+                    .iter_mut()
+                    .for_each(|byte| *byte = self.unread.pop().unwrap());
+                size
+            }
+        };
+
+        self.position += size as u64;
+        Ok(size)
+    }
+}
+
+// Decoding can't claim more memory than the largest chunk accepted: a crafted length can't make
+// bincode allocate gigabytes for a small chunk. The bincode limit is a constant, so it's rounded
+// up to the next one supported, and there is none above 256 MiB.
+fn decode_payload<T: Payload>(bytes: &[u8], max_chunk_size: u64) -> Result<T, Error> {
+    fn decode<T: Payload, const LIMIT: usize>(bytes: &[u8]) -> Result<T, Error> {
+        let config = bincode::config::standard().with_limit::<LIMIT>();
+        Ok(bincode::decode_from_slice(bytes, config)?.0)
+    }
+
+    match max_chunk_size {
+        0..=0x10_0000 => decode::<T, 0x10_0000>(bytes),
+        0x10_0001..=0x100_0000 => decode::<T, 0x100_0000>(bytes),
+        0x100_0001..=0x1000_0000 => decode::<T, 0x1000_0000>(bytes),
+        _ => Ok(bincode::decode_from_slice(bytes, bincode::config::standard())?.0),
+    }
+}
+
+// Nanoseconds, negative for time stamps older than the origin
+fn instant_to_offset(origin: Instant, time_stamp: Instant) -> i64 {
+    match time_stamp.checked_duration_since(origin) {
+        Some(after) => after.as_nanos() as i64,
+        None => -((origin - time_stamp).as_nanos() as i64),
+    }
+}
+
+fn offset_to_instant(origin: Instant, offset: i64) -> Instant {
+    let duration = Duration::from_nanos(offset.unsigned_abs());
+    match offset >= 0 {
+        true => origin + duration,
+        false => origin.checked_sub(duration).unwrap_or(origin), // before boot: clamp
+    }
+}
+
+pub(crate) fn offset_to_system_time(anchor: SystemTime, offset: i64) -> SystemTime {
+    let duration = Duration::from_nanos(offset.unsigned_abs());
+    match offset >= 0 {
+        true => anchor + duration,
+        false => anchor - duration,
+    }
+}
+
+pub(crate) fn system_time_to_offset(anchor: SystemTime, time: SystemTime) -> i64 {
+    system_time_to_nanos(time).saturating_sub(system_time_to_nanos(anchor))
+}
 
-        Ok(ChunkData(buffer, header.time_stamp))
+fn system_time_to_nanos(time: SystemTime) -> i64 {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(after) => after.as_nanos() as i64,
+        Err(before) => -(before.duration().as_nanos() as i64),
     }
 }
 
-unsafe fn any_as_u8_slice<T: Sized>(p: &T) -> &[u8] {
-    unsafe { core::slice::from_raw_parts((p as *const T) as *const u8, size_of::<T>()) }
+fn nanos_to_system_time(nanos: i64) -> SystemTime {
+    offset_to_system_time(SystemTime::UNIX_EPOCH, nanos)
 }
 
-unsafe fn any_as_u8_mut_slice<T: Sized>(p: &mut T) -> &mut [u8] {
-    unsafe { core::slice::from_raw_parts_mut((p as *mut T) as *mut u8, size_of::<T>()) }
+// Saturates, symmetrically so that the offset can be negated: version 1 time stamps are read as
+// they were written
+fn nanos_to_offset(nanos: i128) -> i64 {
+    nanos.clamp(-(i64::MAX as i128), i64::MAX as i128) as i64
+}
+
+// The clock behind Instant, in nanoseconds: what version 1 time stamps were taken on
+fn monotonic_nanos() -> i128 {
+    #[cfg(target_vendor = "apple")]
+    const CLOCK: libc::clockid_t = libc::CLOCK_UPTIME_RAW;
+    #[cfg(not(target_vendor = "apple"))]
+    const CLOCK: libc::clockid_t = libc::CLOCK_MONOTONIC;
+
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // Can't fail, with a supported clock and a valid pointer
+    unsafe { libc::clock_gettime(CLOCK, &mut time) };
+    time.tv_sec as i128 * 1_000_000_000 + time.tv_nsec as i128
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::private::test_tools::{EmptyPayload, TestPayload};
+    use crate::private::test_tools::{EmptyPayload, TempFile, TestPayload};
     use std::time::Duration;
 
     #[test]
@@ -224,8 +890,7 @@ mod tests {
         *bad_header.magic.last_mut().unwrap() = bad_header.magic.last().unwrap() - 1;
         assert_ne!(bad_header.magic, MAGIC);
 
-        let slice = unsafe { any_as_u8_slice(&bad_header) };
-        writer.write_all(slice).unwrap();
+        writer.write_all(&bad_header.encode()).unwrap();
 
         match InputStream::<TestPayload>::new(Box::new(reader)) {
             Err(Error::BadHeader) => {}
@@ -240,8 +905,7 @@ mod tests {
         bad_header.version += 1;
         assert_ne!(bad_header.version, CURRENT_VERSION);
 
-        let slice = unsafe { any_as_u8_slice(&bad_header) };
-        writer.write_all(slice).unwrap();
+        writer.write_all(&bad_header.encode()).unwrap();
 
         match InputStream::<TestPayload>::new(Box::new(reader)) {
             Err(Error::BadVersion(v)) => {
@@ -287,14 +951,18 @@ mod tests {
         let mut istream = InputStream::<TestPayload>::new(Box::new(reader)).unwrap();
 
         let now = Instant::now();
+        let mut first_read = None;
 
         for i in 0..100usize {
-            let time_stamp = now + Duration::from_secs(i as u64);
+            let time_stamp = now + Duration::from_secs(i as u64) + Duration::from_nanos(i as u64);
             let m = Message::new(time_stamp, TestPayload::new(i));
             ostream.append(&m).unwrap();
             let read = istream.get().unwrap();
-            assert_eq!(read.get_type_stamp(), time_stamp);
             read.get_payload().check(i);
+
+            // Time stamps are preserved relative to each other
+            let first_read = *first_read.get_or_insert(read.get_type_stamp());
+            assert_eq!(read.get_type_stamp() - first_read, time_stamp - now);
         }
 
         drop(ostream);
@@ -322,22 +990,429 @@ mod tests {
             istream.get().unwrap().get_payload().check(PAYLOAD_VALUE);
         });
 
-        let encoded = bincode::encode_to_vec(&TestPayload::new(PAYLOAD_VALUE), bincode::config::standard()).unwrap();
+        let encoded =
+            bincode::encode_to_vec(TestPayload::new(PAYLOAD_VALUE), bincode::config::standard())
+                .unwrap();
         let header = ChunkHeader {
-            size: encoded.len(),
-            time_stamp: Instant::now(),
+            marker: CHUNK_MARKER,
+            index: 1,
+            size: encoded.len() as u64,
+            offset: 0,
+            crc: crc32fast::hash(&encoded),
         };
-        let header_slice = unsafe { any_as_u8_slice(&header) };
 
         // Write header one byte at a time
-        for c in header_slice {
+        for c in header.encode() {
             std::thread::sleep(SLEEP_TIME);
-            ostream.write_any(c).unwrap();
+            ostream.raw.write_bytes(&[c]).unwrap();
         }
 
         // Write payload
-        ostream.write_bytes(encoded.as_slice()).unwrap();
+        ostream.raw.write_bytes(encoded.as_slice()).unwrap();
 
         handle.join().unwrap();
     }
+
+    #[test]
+    fn test_read_version_1() {
+        let (reader, mut writer) = os_pipe::pipe().unwrap();
+        let (now, raw_now) = (Instant::now(), monotonic_nanos());
+
+        writer.write_all(&version_1_header()).unwrap();
+        let mut write_chunk = |bytes: &[u8], time_stamp: i128| {
+            let header = ChunkHeaderV1 {
+                size: bytes.len() as u64,
+                time_stamp,
+            };
+            writer.write_all(&header.encode()).unwrap();
+            writer.write_all(bytes).unwrap();
+        };
+
+        write_chunk(TestPayload::format_name().as_bytes(), raw_now);
+        for i in 0..10usize {
+            let encoded =
+                bincode::encode_to_vec(TestPayload::new(i), bincode::config::standard()).unwrap();
+            write_chunk(&encoded, raw_now + i as i128 * 1_000_000);
+        }
+        drop(writer);
+
+        let mut istream = InputStream::<TestPayload>::new(Box::new(reader)).unwrap();
+        let mut first = None;
+        for i in 0..10usize {
+            let read = istream.get().unwrap();
+            read.get_payload().check(i);
+            let first = *first.get_or_insert(read.get_type_stamp());
+            assert_eq!(
+                read.get_type_stamp() - first,
+                Duration::from_millis(i as u64)
+            );
+        }
+        assert!(matches!(istream.get(), Err(Error::RegularEof)));
+
+        // Same boot: mapped back to about the same instant
+        let first = first.unwrap();
+        let error = match first > now {
+            true => first - now,
+            false => now - first,
+        };
+        assert!(error < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_read_version_1_bad_time_stamp() {
+        let mut bytes = version_1_header().to_vec();
+        let format_name = TestPayload::format_name().as_bytes();
+        let mut header = ChunkHeaderV1 {
+            size: format_name.len() as u64,
+            time_stamp: 0,
+        }
+        .encode();
+        header[16..20].copy_from_slice(&4_000_000_000u32.to_ne_bytes());
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(format_name);
+
+        let read = Box::new(std::io::Cursor::new(bytes));
+        match InputStream::<TestPayload>::new(read) {
+            Err(Error::Corrupted { position }) => {
+                assert_eq!(position, StreamHeader::SIZE as u64)
+            }
+            _ => panic!("Expected Corrupted error"),
+        }
+    }
+
+    // As version 1 wrote it, in host order
+    fn version_1_header() -> [u8; StreamHeader::SIZE] {
+        let mut bytes = [0u8; StreamHeader::SIZE];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4..].copy_from_slice(&VERSION_1.to_ne_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_offsets() {
+        let now = Instant::now();
+        for offset in [0, 1, -1, 1_000_000_000_000, -1_000_000] {
+            let time_stamp = offset_to_instant(now, offset);
+            assert_eq!(instant_to_offset(now, time_stamp), offset);
+        }
+    }
+
+    // A stream of 10 messages, and where each of its chunks starts
+    fn recorded_stream() -> (Vec<u8>, Vec<usize>) {
+        let temp_file = TempFile::new().unwrap();
+        let mut ostream =
+            OutputStream::<TestPayload>::new(Box::new(std::fs::File::create(&*temp_file).unwrap()))
+                .unwrap();
+        for i in 0..10usize {
+            ostream
+                .append(&Message::new(Instant::now(), TestPayload::new(i)))
+                .unwrap();
+        }
+        drop(ostream);
+
+        let bytes = std::fs::read(&*temp_file).unwrap();
+        let starts = (0..bytes.len())
+            .filter(|&i| {
+                bytes[i..].starts_with(&CHUNK_MARKER) || bytes[i..].starts_with(&INDEX_MARKER)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(starts.len(), 12); // the last one is the index
+        (bytes, starts)
+    }
+
+    fn read_all(
+        bytes: Vec<u8>,
+        recovery: bool,
+    ) -> (Vec<usize>, Result<(), Error>, Vec<RecordingLoss>) {
+        let mut istream =
+            InputStream::<TestPayload>::new(Box::new(std::io::Cursor::new(bytes))).unwrap();
+        istream.set_recovery(recovery);
+
+        let mut values = Vec::new();
+        let end = loop {
+            match istream.get() {
+                Ok(message) => values.push(message.get_payload().value()),
+                Err(Error::RegularEof) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+
+        (values, end, istream.losses().to_vec())
+    }
+
+    #[test]
+    fn test_corrupted_chunks() {
+        let (bytes, starts) = recorded_stream();
+
+        // Data
+        let mut damaged = bytes.clone();
+        damaged[starts[3] + ChunkHeader::SIZE] ^= 1;
+        let (values, end, _) = read_all(damaged, false);
+        assert_eq!(values, [0, 1]);
+        assert!(matches!(end, Err(Error::Corrupted { position }) if position == starts[3] as u64));
+
+        // Header
+        let mut damaged = bytes.clone();
+        damaged[starts[5] + 12] ^= 1; // size
+        let (values, end, _) = read_all(damaged, false);
+        assert_eq!(values, [0, 1, 2, 3]);
+        assert!(matches!(end, Err(Error::Corrupted { position }) if position == starts[5] as u64));
+
+        // Truncated
+        let (values, end, _) = read_all(bytes[..starts[11] - 1].to_vec(), false);
+        assert_eq!(values.len(), 9);
+        assert!(matches!(end, Err(Error::StdIo(_))));
+    }
+
+    #[test]
+    fn test_recovery() {
+        let (bytes, starts) = recorded_stream();
+        let mut damaged = Vec::new();
+
+        damaged.extend_from_slice(&bytes[..starts[2]]);
+        damaged.extend_from_slice(&bytes[starts[2]..starts[3]]);
+        *damaged.last_mut().unwrap() ^= 1; // data of chunk 2
+        damaged.extend_from_slice(&bytes[starts[3]..starts[5]]);
+        damaged.extend_from_slice(&bytes[starts[5] + 1..starts[7]]); // header of chunk 5
+        damaged.extend_from_slice(&bytes[starts[8]..starts[9]]); // chunk 7 cut out
+        damaged.extend_from_slice(&CHUNK_MARKER); // noise
+        damaged.extend_from_slice(&bytes[starts[9]..starts[11] - 1]); // truncated
+
+        let lost = |from: usize, to: usize, chunks: Range<u64>| RecordingLoss {
+            bytes: from as u64..to as u64,
+            chunks,
+        };
+        // Where the noise starts in the damaged stream
+        let noise = starts[7] - 1 + (starts[9] - starts[8]);
+        let chunk_10 = noise + 4 + (starts[10] - starts[9]);
+
+        let (values, end, losses) = read_all(damaged.clone(), true);
+        assert!(end.is_ok());
+        assert_eq!(values, [0, 2, 3, 5, 7, 8]);
+        assert_eq!(
+            losses,
+            [
+                lost(starts[2], starts[3], 2..3),
+                lost(starts[5], starts[6] - 1, 5..6),
+                lost(starts[7] - 1, starts[7] - 1, 7..8),
+                lost(noise, noise + 4, 9..9),
+                lost(chunk_10, damaged.len(), 10..11),
+            ]
+        );
+    }
+
+    fn seekable_stream(bytes: Vec<u8>) -> InputStream<TestPayload> {
+        InputStream::new_seekable(
+            Box::new(std::io::Cursor::new(bytes)),
+            &ReadLimits::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_seek() {
+        let temp_file = TempFile::new().unwrap();
+        let mut ostream =
+            OutputStream::<TestPayload>::new(Box::new(std::fs::File::create(&*temp_file).unwrap()))
+                .unwrap();
+        let now = Instant::now();
+        for i in 0..200usize {
+            ostream
+                .append(&Message::new(
+                    now + Duration::from_millis(i as u64),
+                    TestPayload::new(i),
+                ))
+                .unwrap();
+        }
+        drop(ostream);
+
+        let bytes = std::fs::read(&*temp_file).unwrap();
+        let index_position =
+            u64::from_le_bytes(bytes[bytes.len() - TRAILER_SIZE..][..8].try_into().unwrap())
+                as usize;
+        let mut without_index = bytes[..index_position].to_vec();
+        let mut bad_trailer = bytes.clone();
+        *bad_trailer.last_mut().unwrap() ^= 1;
+
+        // With the index, without it, and with an unusable one
+        for bytes in [bytes, without_index.clone(), bad_trailer] {
+            let mut istream = seekable_stream(bytes);
+            let (_, origin) = istream.get_with_offset().unwrap();
+
+            istream.seek_to_message(150).unwrap();
+            assert_eq!(istream.get().unwrap().get_payload().value(), 150);
+            istream.seek_to_message(3).unwrap();
+            assert_eq!(istream.get().unwrap().get_payload().value(), 3);
+            istream.seek_to_message(200).unwrap();
+            assert!(matches!(istream.get(), Err(Error::RegularEof)));
+
+            istream.seek_to_offset(origin + 120_000_000).unwrap();
+            assert_eq!(istream.get().unwrap().get_payload().value(), 120);
+            istream.seek_to_offset(origin + 120_500_000).unwrap();
+            assert_eq!(istream.get().unwrap().get_payload().value(), 121);
+            istream.seek_to_offset(i64::MIN).unwrap();
+            assert_eq!(istream.get().unwrap().get_payload().value(), 0);
+        }
+
+        // After a crash, the last chunk may be incomplete
+        without_index.truncate(without_index.len() - 1);
+        let mut istream = seekable_stream(without_index);
+        istream.seek_to_message(198).unwrap();
+        assert_eq!(istream.get().unwrap().get_payload().value(), 198);
+        istream.seek_to_message(199).unwrap();
+        assert!(matches!(istream.get(), Err(Error::StdIo(_))));
+    }
+
+    #[test]
+    fn test_not_seekable() {
+        let (bytes, _) = recorded_stream();
+        let mut istream =
+            InputStream::<TestPayload>::new(Box::new(std::io::Cursor::new(bytes))).unwrap();
+        assert!(matches!(
+            istream.seek_to_message(0),
+            Err(Error::NotSeekable)
+        ));
+        assert_eq!(istream.get().unwrap().get_payload().value(), 0);
+    }
+
+    #[test]
+    fn test_oversized_chunk() {
+        let (bytes, starts) = recorded_stream();
+        let mut crafted = bytes[..starts[1]].to_vec();
+        let header = ChunkHeader {
+            marker: CHUNK_MARKER,
+            index: 1,
+            size: 1 << 40,
+            offset: 0,
+            crc: 0,
+        };
+        crafted.extend_from_slice(&header.encode());
+
+        let get = |limits: ReadLimits| {
+            let read = Box::new(std::io::Cursor::new(crafted.clone()));
+            InputStream::<TestPayload>::new_with(read, &limits)
+                .unwrap()
+                .get()
+        };
+
+        match get(ReadLimits::default()) {
+            Err(Error::ChunkTooLarge { size, .. }) => assert_eq!(size, 1 << 40),
+            _ => panic!("Expected ChunkTooLarge error"),
+        }
+
+        // Without a limit, the missing data is noticed before allocating it
+        match get(ReadLimits::new().max_chunk_size(u64::MAX)) {
+            Err(Error::StdIo(e)) => assert_eq!(e.kind(), ErrorKind::UnexpectedEof),
+            _ => panic!("Expected UnexpectedEof error"),
+        }
+    }
+
+    #[test]
+    fn test_oversized_payload() {
+        #[derive(bincode::Encode, bincode::Decode)]
+        struct Bytes(Vec<u8>);
+        impl Payload for Bytes {}
+
+        let (reader, writer) = os_pipe::pipe().unwrap();
+        let mut ostream = OutputStream::<Bytes>::new(Box::new(writer)).unwrap();
+
+        // A small chunk, announcing 1 TiB of bytes
+        let encoded = bincode::encode_to_vec(1u64 << 40, bincode::config::standard()).unwrap();
+        let header = ChunkHeader {
+            marker: CHUNK_MARKER,
+            index: 1,
+            size: encoded.len() as u64,
+            offset: 0,
+            crc: crc32fast::hash(&encoded),
+        };
+        ostream.raw.write_bytes(&header.encode()).unwrap();
+        ostream.raw.write_bytes(&encoded).unwrap();
+        drop(ostream);
+
+        let mut istream = InputStream::<Bytes>::new(Box::new(reader)).unwrap();
+        match istream.get() {
+            Err(Error::BincodeDecode(bincode::error::DecodeError::LimitExceeded)) => {}
+            _ => panic!("Expected LimitExceeded error"),
+        }
+    }
+
+    #[test]
+    fn test_seek_oversized_chunk() {
+        let (bytes, starts) = recorded_stream();
+        let index_position =
+            u64::from_le_bytes(bytes[bytes.len() - TRAILER_SIZE..][..8].try_into().unwrap())
+                as usize;
+
+        // A valid header crc, but a size that overflows the position
+        let mut crafted = bytes.clone();
+        let header = ChunkHeader {
+            marker: CHUNK_MARKER,
+            index: 1,
+            size: u64::MAX - 10,
+            offset: 0,
+            crc: 0,
+        };
+        crafted[starts[1]..starts[1] + ChunkHeader::SIZE].copy_from_slice(&header.encode());
+        let without_index = crafted[..index_position].to_vec();
+
+        // Through the index, and without it
+        for bytes in [crafted, without_index] {
+            let read = || Box::new(std::io::Cursor::new(bytes.clone()));
+
+            let mut istream =
+                InputStream::<TestPayload>::new_seekable(read(), &ReadLimits::default()).unwrap();
+            match istream.seek_to_message(5) {
+                Err(Error::ChunkTooLarge { size, .. }) => assert_eq!(size, u64::MAX - 10),
+                _ => panic!("Expected ChunkTooLarge error"),
+            }
+
+            let limits = ReadLimits::new().max_chunk_size(u64::MAX);
+            let mut istream = InputStream::<TestPayload>::new_seekable(read(), &limits).unwrap();
+            match istream.seek_to_message(5) {
+                Err(Error::Corrupted { position }) => assert_eq!(position, starts[1] as u64),
+                _ => panic!("Expected Corrupted error"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bad_index_position() {
+        let (bytes, starts) = recorded_stream();
+        let index_position = starts[11];
+
+        // An index entry pointing past the index itself
+        let mut crafted = bytes[..index_position].to_vec();
+        let entry = IndexEntry {
+            chunk: 1,
+            offset: 0,
+            position: u64::MAX,
+        };
+        let header = ChunkHeader {
+            marker: INDEX_MARKER,
+            index: 11,
+            size: IndexEntry::SIZE as u64,
+            offset: 0,
+            crc: crc32fast::hash(&entry.encode()),
+        };
+        crafted.extend_from_slice(&header.encode());
+        crafted.extend_from_slice(&entry.encode());
+        crafted.extend_from_slice(&(index_position as u64).to_le_bytes());
+        crafted.extend_from_slice(&TRAILER_MAGIC);
+
+        // Ignored, and rebuilt from the chunk headers
+        let mut istream = seekable_stream(crafted.clone());
+        assert!(istream.load_index().is_err());
+        istream.seek_to_message(5).unwrap();
+        assert_eq!(istream.get().unwrap().get_payload().value(), 5);
+    }
+
+    #[test]
+    fn test_refuse_version_1() {
+        let read = Box::new(std::io::Cursor::new(version_1_header().to_vec()));
+
+        match InputStream::<TestPayload>::new_with(read, &ReadLimits::new().version_1(false)) {
+            Err(Error::BadVersion(VERSION_1)) => {}
+            _ => panic!("Expected BadVersion error"),
+        }
+    }
 }
@@ -1,6 +1,11 @@
 mod consumer;
+pub(crate) mod history;
 pub(crate) mod io;
 pub(crate) mod queue;
+pub(crate) mod rate;
+pub(crate) mod ring;
+pub(crate) mod signal;
+pub(crate) mod supervisor;
 
 pub(crate) use consumer::Consumer;
 
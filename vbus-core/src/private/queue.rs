@@ -1,7 +1,21 @@
+use crate::ConsumerStats;
 use crate::Message;
+use crate::OverflowPolicy;
 use crate::Payload;
+use crate::private::ring::RingCursor;
 use std::collections::VecDeque;
-use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, RwLock};
+use std::time::{Duration, Instant};
+
+/*
+ * Unbounded queues read the channel ring buffer through their own cursor, so pushing to them only
+ * costs a wake-up when their reader is waiting. Bounded queues apply their overflow policy on
+ * every push and therefore keep their own copy of the pending messages.
+ */
+
+// Called after every push and wake-up, from the pushing or waking thread
+pub(crate) type Hook = Arc<dyn Fn() + Send + Sync>;
 
 pub(crate) struct Queue<T: Payload> {
     queue_data: Arc<QueueData<T>>,
@@ -10,21 +24,43 @@ pub(crate) struct Queue<T: Payload> {
 impl<T: Payload> Queue<T> {
     pub fn new() -> Self {
         Self {
-            queue_data: Arc::new(QueueData::new()),
+            queue_data: Arc::new(QueueData::new(None)),
+        }
+    }
+
+    pub fn new_bounded(capacity: usize, policy: OverflowPolicy) -> Self {
+        assert!(capacity > 0, "bounded queue capacity must be at least 1");
+
+        Self {
+            queue_data: Arc::new(QueueData::new(Some(Bounds { capacity, policy }))),
         }
     }
 
-    pub fn push(&self, message: Message<T>) {
-        self.queue_data.push(message);
+    // Start reading at `start`: messages after it are pending for this queue
+    pub fn attach(&self, start: RingCursor<T>) {
+        self.queue_data.attach(start);
+    }
+
+    // To be called once `message` has been written to the ring the queue is attached to
+    pub fn push(&self, message: &Message<T>) {
+        self.queue_data.push(message, OnFull::Wait);
+    }
+
+    // Like push(), but returns false rather than blocking when full
+    pub fn try_push(&self, message: &Message<T>) -> bool {
+        self.queue_data.push(message, OnFull::GiveUp)
     }
 
-    #[cfg(test)]
     pub fn pull(&self) -> Vec<Message<T>> {
         self.queue_data.pull()
     }
 
     pub fn wait_pull(&self) -> Vec<Message<T>> {
-        self.queue_data.wait_pull()
+        self.queue_data.wait_pull(None)
+    }
+
+    pub fn wait_pull_timeout(&self, timeout: Duration) -> Vec<Message<T>> {
+        self.queue_data.wait_pull(Some(Instant::now() + timeout))
     }
 
     pub fn waker(&self) -> Waker<T> {
@@ -33,10 +69,58 @@ impl<T: Payload> Queue<T> {
         }
     }
 
-    #[cfg(test)]
+    pub fn dropped(&self) -> usize {
+        self.queue_data.dropped()
+    }
+
+    pub fn add_hook(&self, hook: &Hook) {
+        self.queue_data.add_hook(hook);
+    }
+
+    pub fn remove_hook(&self, hook: &Hook) {
+        self.queue_data.remove_hook(hook);
+    }
+
+    // Stop accepting messages and release pushers blocked on a full queue
+    pub fn close(&self) {
+        self.queue_data.close();
+    }
+
     pub fn len(&self) -> usize {
         self.queue_data.len()
     }
+
+    pub fn is_closed(&self) -> bool {
+        self.queue_data.lock().closed
+    }
+
+    // Whether woken up since the last pull or call, for readers that don't wait in wait_pull()
+    pub fn take_woken(&self) -> bool {
+        std::mem::take(&mut self.queue_data.lock().woken)
+    }
+
+    pub fn stats(&self) -> ConsumerStats {
+        self.queue_data.stats()
+    }
+
+    // Pulled messages must then be acknowledged with ack(), rather than on pull
+    pub fn manual_ack(&self) {
+        self.queue_data.lock().manual_ack = true;
+    }
+
+    pub fn ack(&self, count: usize) {
+        self.queue_data.ack(count);
+    }
+
+    // Sequence number that the queue acknowledges once done with everything pushed so far
+    pub fn flush_target(&self) -> u64 {
+        self.queue_data.flush_target()
+    }
+
+    // Returns false if the deadline is reached first
+    pub fn wait_acked(&self, target: u64, deadline: Option<Instant>) -> bool {
+        self.queue_data.wait_acked(target, deadline)
+    }
 }
 
 impl<T: Payload> Clone for Queue<T> {
@@ -55,11 +139,16 @@ impl<T: Payload> PartialEq for Queue<T> {
 
 impl<T: Payload> Eq for Queue<T> {}
 
+/// Wakes up, from any thread, whatever waits on a subscription's queue: a
+/// [`Select`](crate::Select) including the subscription returns `None`. Created by
+/// [`Subscription::waker`](crate::Subscription::waker) and
+/// [`LatestSubscription::waker`](crate::LatestSubscription::waker).
 pub struct Waker<T: Payload> {
     queue_data: Arc<QueueData<T>>,
 }
 
 impl<T: Payload> Waker<T> {
+    /// Cancels the current wait, or the next one if none is in progress.
     pub fn wake_up(&self) {
         self.queue_data.wake_up();
     }
@@ -73,106 +162,385 @@ impl<T: Payload> Clone for Waker<T> {
     }
 }
 
+// What pushing to a full queue with the Block policy does
+#[derive(Clone, Copy, PartialEq)]
+enum OnFull {
+    Wait,
+    DropOldest,
+    GiveUp,
+}
+
+enum Stored {
+    Yes,
+    No,
+    WouldBlock,
+}
+
+#[derive(Clone, Copy)]
+struct Bounds {
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+struct QueueState<T: Payload> {
+    cursor: Option<RingCursor<T>>,  // unbounded queues, once attached
+    messages: VecDeque<Message<T>>, // bounded queues
+    dropped: usize,
+    closed: bool,
+    woken: bool,
+    taken: u64, // pulled or dropped
+    acked: u64, // processed or dropped
+    manual_ack: bool,
+    flushers: usize,
+    high_water: usize, // depth only grows between pulls: peaks are seen when pulling
+    last_pull: Option<Instant>,
+}
+
+impl<T: Payload> QueueState<T> {
+    fn is_empty(&self) -> bool {
+        match &self.cursor {
+            Some(cursor) => cursor.is_empty(),
+            None => self.messages.is_empty(),
+        }
+    }
+}
+
 struct QueueData<T: Payload> {
-    messages: Mutex<VecDeque<Message<T>>>,
+    state: Mutex<QueueState<T>>,
+    bounds: Option<Bounds>,
+    waiters: AtomicUsize,
     condvar: Condvar,
+    not_full: Condvar,
+    acked: Condvar,
+    hooks: RwLock<Vec<Hook>>,
+    hooked: AtomicBool, // avoids taking the hooks lock on every push
 }
 
 impl<T: Payload> QueueData<T> {
-    fn new() -> Self {
+    fn new(bounds: Option<Bounds>) -> Self {
         Self {
-            messages: Mutex::new(VecDeque::<Message<T>>::new()),
+            state: Mutex::new(QueueState {
+                cursor: None,
+                messages: VecDeque::<Message<T>>::new(),
+                dropped: 0,
+                closed: false,
+                woken: false,
+                taken: 0,
+                acked: 0,
+                manual_ack: false,
+                flushers: 0,
+                high_water: 0,
+                last_pull: None,
+            }),
+            bounds,
+            waiters: AtomicUsize::new(0),
             condvar: Condvar::new(),
+            not_full: Condvar::new(),
+            acked: Condvar::new(),
+            hooks: RwLock::new(Vec::new()),
+            hooked: AtomicBool::new(false),
         }
     }
 
-    fn lock(&self) -> MutexGuard<'_, VecDeque<Message<T>>> {
-        self.messages.lock().unwrap()
+    fn lock(&self) -> MutexGuard<'_, QueueState<T>> {
+        self.state.lock().unwrap()
+    }
+
+    fn attach(&self, mut start: RingCursor<T>) {
+        if self.bounds.is_none() {
+            self.lock().cursor = Some(start);
+            return;
+        }
+
+        while let Some(message) = start.next() {
+            self.push(&message, OnFull::DropOldest); // prefill must not block the channel
+        }
     }
 
     fn wake_up(&self) {
-        let _lock = self.lock();
-        self.condvar.notify_all();
+        {
+            let mut lock = self.lock();
+            lock.woken = true;
+            self.condvar.notify_all();
+        }
+
+        self.run_hooks();
     }
 
-    fn push(&self, message: Message<T>) {
+    // Returns false if the message wasn't pushed rather than blocking
+    fn push(&self, message: &Message<T>, on_full: OnFull) -> bool {
+        let Some(bounds) = self.bounds else {
+            // Already in the ring: the reader only needs a wake-up, and only if it's waiting
+            if self.waiters.load(Ordering::SeqCst) > 0 {
+                let _lock = self.lock();
+                self.condvar.notify_all();
+            }
+            self.run_hooks();
+            return true;
+        };
+
+        match self.store(message, bounds, on_full) {
+            Stored::Yes => self.run_hooks(),
+            Stored::No => {}
+            Stored::WouldBlock => return false,
+        }
+        true
+    }
+
+    fn store(&self, message: &Message<T>, bounds: Bounds, on_full: OnFull) -> Stored {
         let mut lock = self.lock();
 
-        lock.push_back(message);
-        self.condvar.notify_all();
+        let policy = match bounds.policy {
+            OverflowPolicy::Block if on_full == OnFull::DropOldest => OverflowPolicy::DropOldest,
+            policy => policy,
+        };
+
+        match policy {
+            OverflowPolicy::DropOldest => {
+                while lock.messages.len() >= bounds.capacity {
+                    lock.messages.pop_front();
+                    lock.dropped += 1;
+                    lock.taken += 1;
+                    self.do_ack(&mut lock, 1);
+                }
+            }
+            OverflowPolicy::DropNewest => {
+                if lock.messages.len() >= bounds.capacity {
+                    lock.dropped += 1;
+                    return Stored::No;
+                }
+            }
+            OverflowPolicy::Block => {
+                while lock.messages.len() >= bounds.capacity && !lock.closed {
+                    if on_full == OnFull::GiveUp {
+                        return Stored::WouldBlock;
+                    }
+                    lock = self.not_full.wait(lock).unwrap();
+                }
+            }
+        }
+
+        if lock.closed {
+            return Stored::No;
+        }
+
+        lock.messages.push_back(message.clone());
+
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            self.condvar.notify_all();
+        }
+
+        Stored::Yes
+    }
+
+    fn run_hooks(&self) {
+        if self.hooked.load(Ordering::SeqCst) {
+            self.hooks.read().unwrap().iter().for_each(|hook| hook());
+        }
+    }
+
+    fn add_hook(&self, hook: &Hook) {
+        let mut hooks = self.hooks.write().unwrap();
+        hooks.push(hook.clone());
+        self.hooked.store(true, Ordering::SeqCst);
+    }
+
+    fn remove_hook(&self, hook: &Hook) {
+        let mut hooks = self.hooks.write().unwrap();
+        hooks.retain(|h| !Arc::ptr_eq(h, hook));
+        self.hooked.store(!hooks.is_empty(), Ordering::SeqCst);
     }
 
-    #[cfg(test)]
     fn pull(&self) -> Vec<Message<T>> {
         let mut v = Vec::<Message<T>>::new();
 
         {
             let mut lock = self.lock();
-            Self::do_pull(&mut lock, &mut v);
+            self.do_pull(&mut lock, &mut v);
         }
 
         v
     }
 
-    fn wait_pull(&self) -> Vec<Message<T>> {
+    // Returns an empty vector if woken up or if the deadline is reached
+    fn wait_pull(&self, deadline: Option<Instant>) -> Vec<Message<T>> {
         let mut v = Vec::<Message<T>>::new();
 
         {
             let mut lock = self.lock();
-            Self::do_pull(&mut lock, &mut v);
-            if v.is_empty() {
-                Self::do_pull(&mut self.condvar.wait(lock).unwrap(), &mut v);
+
+            // SeqCst: a pusher either sees us waiting, or we see its message
+            self.waiters.fetch_add(1, Ordering::SeqCst);
+
+            while lock.is_empty() && !lock.woken {
+                lock = match deadline {
+                    None => self.condvar.wait(lock).unwrap(),
+                    Some(deadline) => {
+                        let now = Instant::now();
+                        if now >= deadline {
+                            break;
+                        }
+                        self.condvar.wait_timeout(lock, deadline - now).unwrap().0
+                    }
+                };
             }
+
+            self.waiters.fetch_sub(1, Ordering::SeqCst);
+
+            lock.woken = false;
+            self.do_pull(&mut lock, &mut v);
         }
 
         v
     }
 
-    fn do_pull(lock: &mut MutexGuard<VecDeque<Message<T>>>, output: &mut Vec<Message<T>>) {
-        while let Some(msg) = lock.pop_front() {
-            output.push(msg);
+    fn do_pull(&self, lock: &mut MutexGuard<QueueState<T>>, output: &mut Vec<Message<T>>) {
+        let len = output.len();
+
+        if let Some(cursor) = &mut lock.cursor {
+            output.extend(std::iter::from_fn(|| cursor.next()));
+        } else {
+            output.extend(lock.messages.drain(..));
+
+            if self
+                .bounds
+                .is_some_and(|b| b.policy == OverflowPolicy::Block)
+            {
+                self.not_full.notify_all();
+            }
+        }
+
+        let pulled = output.len() - len;
+        lock.taken += pulled as u64;
+        lock.high_water = lock.high_water.max(pulled);
+        lock.last_pull = Some(Instant::now());
+        if !lock.manual_ack {
+            self.do_ack(lock, pulled);
+        }
+    }
+
+    fn dropped(&self) -> usize {
+        self.lock().dropped
+    }
+
+    fn close(&self) {
+        let mut lock = self.lock();
+        lock.closed = true;
+        if let Some(cursor) = &mut lock.cursor {
+            cursor.freeze();
         }
+        self.not_full.notify_all();
+        self.acked.notify_all(); // nobody will acknowledge anymore
     }
 
-    #[cfg(test)]
     fn len(&self) -> usize {
-        self.lock().len()
+        Self::pending(&self.lock())
+    }
+
+    fn pending(lock: &QueueState<T>) -> usize {
+        match &lock.cursor {
+            Some(cursor) => cursor.len(),
+            None => lock.messages.len(),
+        }
+    }
+
+    fn stats(&self) -> ConsumerStats {
+        let lock = self.lock();
+        let depth = Self::pending(&lock);
+
+        ConsumerStats {
+            depth,
+            high_water_mark: lock.high_water.max(depth),
+            dropped: lock.dropped,
+            since_last_pull: lock.last_pull.map(|t| t.elapsed()),
+        }
+    }
+
+    fn ack(&self, count: usize) {
+        let mut lock = self.lock();
+        self.do_ack(&mut lock, count);
+    }
+
+    fn do_ack(&self, lock: &mut MutexGuard<QueueState<T>>, count: usize) {
+        lock.acked += count as u64;
+        if lock.flushers > 0 {
+            self.acked.notify_all();
+        }
+    }
+
+    fn flush_target(&self) -> u64 {
+        let lock = self.lock();
+        lock.taken + Self::pending(&lock) as u64
+    }
+
+    fn wait_acked(&self, target: u64, deadline: Option<Instant>) -> bool {
+        let mut lock = self.lock();
+        lock.flushers += 1;
+
+        while lock.acked < target && !lock.closed {
+            lock = match deadline {
+                None => self.acked.wait(lock).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    self.acked.wait_timeout(lock, deadline - now).unwrap().0
+                }
+            };
+        }
+
+        lock.flushers -= 1;
+        lock.acked >= target || lock.closed
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::private::ring::Ring;
     use crate::private::test_tools::TestPayload;
     use std::thread::sleep;
-    use std::time::{Duration, Instant};
 
-    fn push_many(queue: &Queue<TestPayload>, n: usize) {
+    // What a channel does with its queues
+    fn attached(queue: Queue<TestPayload>) -> (Ring<TestPayload>, Queue<TestPayload>) {
+        let ring = Ring::new();
+        queue.attach(ring.cursor());
+        (ring, queue)
+    }
+
+    fn push(ring: &Ring<TestPayload>, queue: &Queue<TestPayload>, payload: TestPayload) {
+        let message = Message::new(Instant::now(), payload);
+        ring.push(message.clone());
+        queue.push(&message);
+    }
+
+    fn push_many(ring: &Ring<TestPayload>, queue: &Queue<TestPayload>, n: usize) {
         for i in 0..n {
-            queue.push(Message::new(Instant::now(), TestPayload::new(i)));
+            push(ring, queue, TestPayload::new(i));
         }
     }
 
     fn check_many(messages: &[Message<TestPayload>], n: usize) {
         assert_eq!(messages.len(), n);
-        for i in 0..n {
-            messages[i].get_payload().check(i);
+        for (i, message) in messages.iter().enumerate() {
+            message.get_payload().check(i);
         }
     }
 
     #[test]
     fn test_pull_and_wait_pull() {
-        let queue = Queue::new();
+        let (ring, queue) = attached(Queue::new());
 
         for n in 1..10 {
             assert_eq!(queue.pull().len(), 0);
 
-            push_many(&queue, n);
+            push_many(&ring, &queue, n);
             check_many(&queue.pull(), n);
 
             assert_eq!(queue.pull().len(), 0);
 
-            push_many(&queue, n);
+            push_many(&ring, &queue, n);
             check_many(&queue.wait_pull(), n);
         }
 
@@ -183,13 +551,13 @@ mod tests {
     fn test_wait_pull() {
         const WAIT_TIME: Duration = Duration::from_millis(200);
 
-        let queue = Queue::new();
+        let (ring, queue) = attached(Queue::new());
         let start_time = Instant::now();
 
         let queue_for_thread = queue.clone();
         let join_handle = std::thread::spawn(move || {
             sleep(WAIT_TIME);
-            queue_for_thread.push(Message::new(Instant::now(), TestPayload::default()));
+            push(&ring, &queue_for_thread, TestPayload::default());
         });
 
         let v = queue.wait_pull();
@@ -224,6 +592,232 @@ mod tests {
         assert!(v.is_empty());
     }
 
+    #[test]
+    fn test_wait_pull_timeout() {
+        const WAIT_TIME: Duration = Duration::from_millis(200);
+
+        let (ring, queue) = attached(Queue::new());
+
+        let start_time = Instant::now();
+        assert!(queue.wait_pull_timeout(WAIT_TIME).is_empty());
+        assert!(start_time.elapsed() >= WAIT_TIME);
+
+        push_many(&ring, &queue, 3);
+        let start_time = Instant::now();
+        check_many(&queue.wait_pull_timeout(WAIT_TIME), 3);
+        assert!(start_time.elapsed() < WAIT_TIME);
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let (ring, queue) = attached(Queue::new_bounded(3, OverflowPolicy::DropOldest));
+
+        push_many(&ring, &queue, 5);
+        assert_eq!(queue.dropped(), 2);
+
+        let v = queue.pull();
+        assert_eq!(v.len(), 3);
+        for (i, message) in v.iter().enumerate() {
+            message.get_payload().check(i + 2);
+        }
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        let (ring, queue) = attached(Queue::new_bounded(3, OverflowPolicy::DropNewest));
+
+        push_many(&ring, &queue, 5);
+        assert_eq!(queue.dropped(), 2);
+        check_many(&queue.pull(), 3);
+
+        push_many(&ring, &queue, 1);
+        assert_eq!(queue.dropped(), 2);
+        check_many(&queue.pull(), 1);
+    }
+
+    #[test]
+    fn test_block() {
+        const WAIT_TIME: Duration = Duration::from_millis(200);
+
+        let (ring, queue) = attached(Queue::new_bounded(2, OverflowPolicy::Block));
+        push_many(&ring, &queue, 2);
+
+        let queue_for_thread = queue.clone();
+        let start_time = Instant::now();
+        let join_handle = std::thread::spawn(move || {
+            push(&ring, &queue_for_thread, TestPayload::new(2));
+            start_time.elapsed()
+        });
+
+        sleep(WAIT_TIME);
+        assert_eq!(queue.len(), 2);
+        check_many(&queue.pull(), 2);
+
+        let elapsed = join_handle.join().unwrap();
+        assert!(elapsed >= WAIT_TIME);
+        assert_eq!(queue.dropped(), 0);
+        queue.pull().first().unwrap().get_payload().check(2);
+    }
+
+    #[test]
+    fn test_close_releases_blocked_pusher() {
+        let (ring, queue) = attached(Queue::new_bounded(1, OverflowPolicy::Block));
+        push_many(&ring, &queue, 1);
+
+        let queue_for_thread = queue.clone();
+        let join_handle = std::thread::spawn(move || {
+            push(&ring, &queue_for_thread, TestPayload::default());
+        });
+
+        sleep(Duration::from_millis(100));
+        queue.close();
+        join_handle.join().unwrap();
+
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn test_prefill_does_not_block() {
+        let ring = Ring::new();
+        let start = ring.cursor();
+        for i in 0..5 {
+            ring.push(Message::new(Instant::now(), TestPayload::new(i)));
+        }
+
+        let queue = Queue::new_bounded(2, OverflowPolicy::Block);
+        queue.attach(start);
+        assert_eq!(queue.dropped(), 3);
+
+        let v = queue.pull();
+        assert_eq!(v.len(), 2);
+        v[0].get_payload().check(3);
+        v[1].get_payload().check(4);
+    }
+
+    #[test]
+    fn test_attach_replays_from_cursor() {
+        let ring = Ring::new();
+        let start = ring.cursor();
+        for i in 0..3 {
+            ring.push(Message::new(Instant::now(), TestPayload::new(i)));
+        }
+
+        let unbounded = Queue::new();
+        unbounded.attach(start.clone());
+        check_many(&unbounded.pull(), 3);
+
+        let bounded = Queue::new_bounded(10, OverflowPolicy::DropOldest);
+        bounded.attach(start);
+        check_many(&bounded.pull(), 3);
+    }
+
+    #[test]
+    fn test_close() {
+        for queue in [Queue::new(), Queue::new_bounded(10, OverflowPolicy::Block)] {
+            let (ring, queue) = attached(queue);
+
+            push_many(&ring, &queue, 2);
+            queue.close();
+            push(&ring, &queue, TestPayload::new(2));
+
+            assert_eq!(queue.len(), 2);
+            check_many(&queue.pull(), 2);
+            assert!(queue.pull().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_acks() {
+        for queue in [
+            Queue::new(),
+            Queue::new_bounded(2, OverflowPolicy::DropOldest),
+        ] {
+            let (ring, queue) = attached(queue);
+            queue.manual_ack();
+
+            push_many(&ring, &queue, 3); // one dropped by the bounded queue
+            let target = queue.flush_target();
+            let pulled = queue.pull().len();
+            assert!(!queue.wait_acked(target, Some(Instant::now())));
+
+            push_many(&ring, &queue, 1); // pushed after the flush target
+            queue.ack(pulled);
+            assert!(queue.wait_acked(target, None));
+            assert!(!queue.wait_acked(queue.flush_target(), Some(Instant::now())));
+
+            queue.close();
+            assert!(queue.wait_acked(queue.flush_target(), None));
+        }
+
+        let (ring, queue) = attached(Queue::new());
+        push_many(&ring, &queue, 3);
+        let target = queue.flush_target();
+        queue.pull(); // acknowledges by default
+        assert!(queue.wait_acked(target, Some(Instant::now())));
+    }
+
+    #[test]
+    fn test_stats() {
+        for queue in [
+            Queue::new(),
+            Queue::new_bounded(4, OverflowPolicy::DropOldest),
+        ] {
+            let (ring, queue) = attached(queue);
+
+            let stats = queue.stats();
+            assert_eq!(stats.depth, 0);
+            assert_eq!(stats.high_water_mark, 0);
+            assert!(stats.since_last_pull.is_none());
+
+            push_many(&ring, &queue, 3);
+            assert_eq!(queue.stats().depth, 3);
+            assert_eq!(queue.stats().high_water_mark, 3);
+
+            queue.pull();
+            push_many(&ring, &queue, 1);
+            sleep(Duration::from_millis(10));
+
+            let stats = queue.stats();
+            assert_eq!(stats.depth, 1);
+            assert_eq!(stats.high_water_mark, 3);
+            assert_eq!(stats.dropped, 0);
+            assert!(stats.since_last_pull.unwrap() >= Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_hooks() {
+        let (ring, queue) = attached(Queue::new());
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_for_hook = counter.clone();
+        let hook: Hook = Arc::new(move || {
+            counter_for_hook.fetch_add(1, Ordering::SeqCst);
+        });
+
+        queue.add_hook(&hook);
+        push_many(&ring, &queue, 3);
+        queue.waker().wake_up();
+        assert_eq!(counter.load(Ordering::SeqCst), 4);
+
+        queue.remove_hook(&hook);
+        push_many(&ring, &queue, 3);
+        assert_eq!(counter.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_hooks_bounded() {
+        let (ring, queue) = attached(Queue::new_bounded(1, OverflowPolicy::DropNewest));
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_for_hook = counter.clone();
+        let hook: Hook = Arc::new(move || {
+            counter_for_hook.fetch_add(1, Ordering::SeqCst);
+        });
+
+        queue.add_hook(&hook);
+        push_many(&ring, &queue, 3); // only the first one is kept
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
     #[test]
     fn test_eq() {
         let a = Queue::<TestPayload>::new();
@@ -0,0 +1,98 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/*
+ * Sliding window event counter: the window is split in buckets, each packing the number of the
+ * time slice it currently counts (upper half) with its count (lower half), so that recording an
+ * event is a single atomic operation in the common case.
+ */
+
+const BUCKETS: usize = 10;
+const SLICE: Duration = Duration::from_millis(100);
+pub(crate) const WINDOW: Duration = Duration::from_millis(100 * BUCKETS as u64);
+
+pub(crate) struct RateMeter {
+    origin: Instant,
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl RateMeter {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            buckets: Default::default(),
+        }
+    }
+
+    pub fn record(&self, at: Instant) {
+        let slice = self.slice(at);
+        let bucket = &self.buckets[slice as usize % BUCKETS];
+
+        let mut current = bucket.load(Ordering::Relaxed);
+        loop {
+            // A racing thread may already be in the next slice: close enough
+            if current >> 32 >= slice {
+                bucket.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+
+            // Stale bucket: restart it for this slice, unless someone else just did
+            match bucket.compare_exchange_weak(
+                current,
+                (slice << 32) | 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    // Events per second over the last WINDOW, the current slice included
+    pub fn rate(&self, now: Instant) -> f64 {
+        let slice = self.slice(now);
+
+        let count: u64 = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .filter(|packed| {
+                (slice.saturating_sub(BUCKETS as u64) + 1..=slice).contains(&(packed >> 32))
+            })
+            .map(|packed| packed & u32::MAX as u64)
+            .sum();
+
+        count as f64 / WINDOW.as_secs_f64()
+    }
+
+    fn slice(&self, at: Instant) -> u64 {
+        // +1: an untouched bucket (slice 0) never looks current
+        (at.saturating_duration_since(self.origin).as_millis() / SLICE.as_millis()) as u64 + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate() {
+        let meter = RateMeter::new();
+        let start = meter.origin;
+
+        assert_eq!(meter.rate(start), 0.0);
+
+        (0..100).for_each(|_| meter.record(start));
+        (0..50).for_each(|_| meter.record(start + Duration::from_millis(550)));
+        assert_eq!(meter.rate(start + Duration::from_millis(600)), 150.0);
+
+        // The first slice leaves the window
+        assert_eq!(meter.rate(start + WINDOW), 50.0);
+        assert_eq!(meter.rate(start + WINDOW * 2), 0.0);
+
+        // Bucket reuse
+        meter.record(start + WINDOW);
+        assert_eq!(meter.rate(start + WINDOW), 51.0);
+    }
+}
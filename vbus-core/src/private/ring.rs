@@ -0,0 +1,270 @@
+use crate::{Message, Payload};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/*
+ * Shared broadcast buffer: every message pushed to a channel is written once, and each reader
+ * walks the buffer with its own cursor. Storage is a chain of fixed-size blocks: writers fill the
+ * tail block under a mutex, readers only ever do atomic loads, and a block is freed as soon as no
+ * cursor refers to it anymore.
+ *
+ * This is not lock-free: pushes are serialized on the tail, and the channel still visits every
+ * queue on each push to wake up waiting readers and run hooks (an atomic load per idle reader).
+ */
+
+const BLOCK_SIZE: usize = 64;
+
+pub(crate) struct Ring<T: Payload> {
+    ring_data: Arc<RingData<T>>,
+}
+
+impl<T: Payload> Ring<T> {
+    pub fn new() -> Self {
+        let block = Arc::new(Block::new());
+
+        Self {
+            ring_data: Arc::new(RingData {
+                tail: Mutex::new(Tail { block, index: 0 }),
+                written: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn push(&self, message: Message<T>) {
+        let mut tail = self.ring_data.tail.lock().unwrap();
+
+        if tail.index == BLOCK_SIZE {
+            let block = Arc::new(Block::new());
+            let _ = tail.block.next.set(block.clone());
+            tail.block = block;
+            tail.index = 0;
+        }
+
+        let _ = tail.block.slots[tail.index].set(message);
+        tail.index += 1;
+
+        // SeqCst: pairs with readers announcing that they are about to wait
+        self.ring_data.written.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // Cursor positioned after the last written message
+    pub fn cursor(&self) -> RingCursor<T> {
+        let tail = self.ring_data.tail.lock().unwrap();
+
+        RingCursor {
+            ring_data: self.ring_data.clone(),
+            block: tail.block.clone(),
+            index: tail.index,
+            position: self.ring_data.written.load(Ordering::SeqCst),
+            end: None,
+        }
+    }
+
+    pub fn written(&self) -> u64 {
+        self.ring_data.written.load(Ordering::SeqCst)
+    }
+}
+
+impl<T: Payload> Clone for Ring<T> {
+    fn clone(&self) -> Self {
+        Self {
+            ring_data: self.ring_data.clone(),
+        }
+    }
+}
+
+pub(crate) struct RingCursor<T: Payload> {
+    ring_data: Arc<RingData<T>>,
+    block: Arc<Block<T>>,
+    index: usize,
+    position: u64,
+    end: Option<u64>, // set once frozen
+}
+
+impl<T: Payload> RingCursor<T> {
+    pub fn next(&mut self) -> Option<Message<T>> {
+        if self.end.is_some_and(|end| self.position >= end) {
+            return None;
+        }
+
+        if self.index == BLOCK_SIZE {
+            self.block = self.block.next.get()?.clone();
+            self.index = 0;
+        }
+
+        let message = self.block.slots[self.index].get()?.clone();
+        self.index += 1;
+        self.position += 1;
+
+        Some(message)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Number of messages written after the cursor
+    pub fn len(&self) -> usize {
+        // A slot is readable shortly before the writer bumps the count: saturate
+        let written = self.ring_data.written.load(Ordering::SeqCst);
+        (self.end.unwrap_or(written).min(written)).saturating_sub(self.position) as usize
+    }
+
+    // Ignore messages written from now on
+    pub fn freeze(&mut self) {
+        if self.end.is_none() {
+            self.end = Some(self.ring_data.written.load(Ordering::SeqCst));
+        }
+    }
+}
+
+impl<T: Payload> Clone for RingCursor<T> {
+    fn clone(&self) -> Self {
+        Self {
+            ring_data: self.ring_data.clone(),
+            block: self.block.clone(),
+            index: self.index,
+            position: self.position,
+            end: self.end,
+        }
+    }
+}
+
+struct RingData<T: Payload> {
+    tail: Mutex<Tail<T>>,
+    written: AtomicU64,
+}
+
+struct Tail<T: Payload> {
+    block: Arc<Block<T>>,
+    index: usize,
+}
+
+struct Block<T: Payload> {
+    slots: [OnceLock<Message<T>>; BLOCK_SIZE],
+    next: OnceLock<Arc<Block<T>>>,
+}
+
+impl<T: Payload> Block<T> {
+    fn new() -> Self {
+        Self {
+            slots: std::array::from_fn(|_| OnceLock::new()),
+            next: OnceLock::new(),
+        }
+    }
+}
+
+impl<T: Payload> Drop for Block<T> {
+    // Unlink iteratively: the default recursive drop overflows the stack on long chains
+    fn drop(&mut self) {
+        let mut next = self.next.take();
+
+        while let Some(block) = next {
+            next = match Arc::try_unwrap(block) {
+                Ok(mut block) => block.next.take(),
+                Err(_) => None, // still referenced by a cursor
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private::test_tools::TestPayload;
+    use std::time::Instant;
+
+    fn push_many(ring: &Ring<TestPayload>, range: std::ops::Range<usize>) {
+        for i in range {
+            ring.push(Message::new(Instant::now(), TestPayload::new(i)));
+        }
+    }
+
+    fn read_all(cursor: &mut RingCursor<TestPayload>) -> Vec<usize> {
+        std::iter::from_fn(|| cursor.next())
+            .map(|m| m.get_payload().value())
+            .collect()
+    }
+
+    #[test]
+    fn test_cursor_starts_at_end() {
+        let ring = Ring::<TestPayload>::new();
+        push_many(&ring, 0..10);
+
+        let mut cursor = ring.cursor();
+        assert!(cursor.is_empty());
+        assert!(cursor.next().is_none());
+
+        push_many(&ring, 10..12);
+        assert_eq!(cursor.len(), 2);
+        assert_eq!(read_all(&mut cursor), [10, 11]);
+        assert!(cursor.is_empty());
+    }
+
+    #[test]
+    fn test_freeze() {
+        let ring = Ring::<TestPayload>::new();
+        let mut cursor = ring.cursor();
+
+        push_many(&ring, 0..3);
+        cursor.freeze();
+        push_many(&ring, 3..5);
+
+        assert_eq!(cursor.len(), 3);
+        assert_eq!(read_all(&mut cursor), [0, 1, 2]);
+        assert!(cursor.is_empty());
+    }
+
+    #[test]
+    fn test_across_blocks() {
+        let ring = Ring::<TestPayload>::new();
+        let mut a = ring.cursor();
+
+        push_many(&ring, 0..BLOCK_SIZE * 3 + 1);
+        let mut b = a.clone();
+
+        let expected: Vec<usize> = (0..BLOCK_SIZE * 3 + 1).collect();
+        assert_eq!(read_all(&mut a), expected);
+        assert_eq!(read_all(&mut b), expected);
+        assert_eq!(ring.written(), expected.len() as u64);
+    }
+
+    #[test]
+    fn test_long_chain_drop() {
+        let ring = Ring::<TestPayload>::new();
+        let cursor = ring.cursor();
+
+        push_many(&ring, 0..BLOCK_SIZE * 20_000);
+
+        drop(ring);
+        drop(cursor); // must not overflow the stack
+    }
+
+    #[test]
+    fn test_concurrent_readers() {
+        const N: usize = 10_000;
+
+        let ring = Ring::<TestPayload>::new();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let mut cursor = ring.cursor();
+                std::thread::spawn(move || {
+                    let mut v = Vec::new();
+                    while v.len() < N {
+                        if let Some(m) = cursor.next() {
+                            v.push(m.get_payload().value());
+                        }
+                    }
+                    v
+                })
+            })
+            .collect();
+
+        push_many(&ring, 0..N);
+
+        let expected: Vec<usize> = (0..N).collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::{Condvar, Mutex};
+use std::time::Instant;
+
+// Raised by queue hooks, for waiting on several queues at once
+#[derive(Default)]
+pub(crate) struct Signal {
+    raised: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl Signal {
+    pub fn raise(&self) {
+        *self.raised.lock().unwrap() = true;
+        self.condvar.notify_all();
+    }
+
+    // Returns false if the deadline is reached first
+    pub fn wait(&self, deadline: Option<Instant>) -> bool {
+        let mut raised = self.raised.lock().unwrap();
+
+        while !*raised {
+            raised = match deadline {
+                None => self.condvar.wait(raised).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    self.condvar.wait_timeout(raised, deadline - now).unwrap().0
+                }
+            };
+        }
+
+        *raised = false;
+        true
+    }
+}
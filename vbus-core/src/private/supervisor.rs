@@ -0,0 +1,217 @@
+use crate::{Message, Payload, Supervision};
+use std::any::Any;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub(crate) type Process<T> = Box<dyn FnMut(Vec<Message<T>>) + Send>;
+pub(crate) type MakeProcess<T> = Box<dyn FnMut() -> Process<T> + Send>;
+pub(crate) type PanicHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+// Runs the process closure of a threaded consumer, applying its supervision policy on panic
+pub(crate) struct Supervisor<T: Payload> {
+    supervision: Supervision,
+    make_process: Option<MakeProcess<T>>, // None -> can't be rebuilt
+    process: Process<T>,
+    panics: Arc<Panics>,
+    on_panic: Option<PanicHandler>, // the panic hook has reported the panic already
+}
+
+impl<T: Payload> Supervisor<T> {
+    pub fn new(
+        supervision: Supervision,
+        process: impl FnMut(Vec<Message<T>>) + Send + 'static,
+    ) -> Self {
+        Self {
+            supervision,
+            make_process: None,
+            process: Box::new(process),
+            panics: Arc::new(Panics::default()),
+            on_panic: None,
+        }
+    }
+
+    pub fn new_restartable<P>(
+        supervision: Supervision,
+        mut make_process: impl FnMut() -> P + Send + 'static,
+    ) -> Self
+    where
+        P: FnMut(Vec<Message<T>>) + Send + 'static,
+    {
+        let process = Box::new(make_process());
+
+        Self {
+            supervision,
+            make_process: Some(Box::new(move || Box::new(make_process()))),
+            process,
+            panics: Arc::new(Panics::default()),
+            on_panic: None,
+        }
+    }
+
+    pub fn on_panic(mut self, on_panic: Option<PanicHandler>) -> Self {
+        self.on_panic = on_panic;
+        self
+    }
+
+    pub fn panics(&self) -> Arc<Panics> {
+        self.panics.clone()
+    }
+
+    // Returns false if the panic is to be propagated: processing must stop
+    pub fn process(&mut self, messages: Vec<Message<T>>) -> bool {
+        let Err(payload) = catch_unwind(AssertUnwindSafe(|| (self.process)(messages))) else {
+            return true;
+        };
+
+        let message = panic_message(payload.as_ref());
+        self.panics.record(message.clone());
+
+        match self.supervision {
+            Supervision::Propagate => {
+                *self.panics.payload.lock().unwrap() = Some(payload);
+                return false;
+            }
+            Supervision::LogAndSkip => {
+                if let Some(on_panic) = &self.on_panic {
+                    on_panic(&message);
+                }
+            }
+            Supervision::Restart => {
+                if let Some(on_panic) = &self.on_panic {
+                    on_panic(&message);
+                }
+                if let Some(make_process) = &mut self.make_process {
+                    self.process = make_process();
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Panics {
+    count: AtomicUsize,
+    last: Mutex<Option<String>>,
+    payload: Mutex<Option<Box<dyn Any + Send>>>, // to be propagated
+}
+
+impl Panics {
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    pub fn last(&self) -> Option<String> {
+        self.last.lock().unwrap().clone()
+    }
+
+    pub fn take_payload(&self) -> Option<Box<dyn Any + Send>> {
+        self.payload.lock().unwrap().take()
+    }
+
+    fn record(&self, message: String) {
+        *self.last.lock().unwrap() = Some(message);
+        self.count.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private::test_tools::TestPayload;
+    use std::time::Instant;
+
+    fn batch(value: usize) -> Vec<Message<TestPayload>> {
+        vec![Message::new(Instant::now(), TestPayload::new(value))]
+    }
+
+    fn panic_on_zero(messages: Vec<Message<TestPayload>>) {
+        if messages[0].get_payload().value() == 0 {
+            panic!("zero");
+        }
+    }
+
+    #[test]
+    fn test_log_and_skip() {
+        let mut supervisor = Supervisor::new(Supervision::LogAndSkip, panic_on_zero);
+
+        assert!(supervisor.process(batch(1)));
+        assert!(supervisor.process(batch(0)));
+        assert!(supervisor.process(batch(1)));
+
+        let panics = supervisor.panics();
+        assert_eq!(panics.count(), 1);
+        assert_eq!(panics.last().unwrap(), "zero");
+        assert!(panics.take_payload().is_none());
+    }
+
+    #[test]
+    fn test_on_panic() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let reported_for_handler = reported.clone();
+        let handler: PanicHandler = Arc::new(move |message: &str| {
+            reported_for_handler
+                .lock()
+                .unwrap()
+                .push(message.to_string())
+        });
+
+        let mut supervisor =
+            Supervisor::new(Supervision::LogAndSkip, panic_on_zero).on_panic(Some(handler));
+        assert!(supervisor.process(batch(0)));
+        assert!(supervisor.process(batch(1)));
+
+        assert_eq!(reported.lock().unwrap().as_slice(), ["zero"]);
+    }
+
+    #[test]
+    fn test_propagate() {
+        let mut supervisor = Supervisor::new(Supervision::Propagate, panic_on_zero);
+
+        assert!(!supervisor.process(batch(0)));
+
+        let panics = supervisor.panics();
+        assert_eq!(panics.count(), 1);
+        let payload = panics.take_payload().unwrap();
+        assert_eq!(*payload.downcast_ref::<&str>().unwrap(), "zero");
+    }
+
+    #[test]
+    fn test_restart() {
+        let made = Arc::new(AtomicUsize::new(0));
+        let made_for_factory = made.clone();
+
+        let mut supervisor = Supervisor::new_restartable(Supervision::Restart, move || {
+            made_for_factory.fetch_add(1, Ordering::SeqCst);
+            let mut seen = 0;
+            move |messages: Vec<Message<TestPayload>>| {
+                seen += messages.len();
+                assert!(seen < 2, "state must be reset: {seen}");
+            }
+        });
+        assert_eq!(made.load(Ordering::SeqCst), 1);
+
+        assert!(supervisor.process(batch(0)));
+        assert!(supervisor.process(batch(0))); // panics, state is rebuilt
+        assert!(supervisor.process(batch(0)));
+
+        assert_eq!(made.load(Ordering::SeqCst), 2);
+        assert_eq!(supervisor.panics().count(), 1);
+        assert_eq!(
+            supervisor.panics().last().unwrap(),
+            "state must be reset: 2"
+        );
+    }
+}
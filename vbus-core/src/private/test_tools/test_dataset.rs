@@ -50,13 +50,19 @@ fn test_dataset() {
     let file = File::open(path).unwrap();
     let mut istream = InputStream::<TestPayload>::new(Box::new(file)).unwrap();
 
+    let mut first_read = None;
     for ref_message in messages.iter() {
         let read_message = istream.get().unwrap();
         assert_eq!(
             read_message.get_payload().value(),
             ref_message.get_payload().value()
         );
-        assert_eq!(read_message.get_type_stamp(), ref_message.get_type_stamp());
+
+        let first_read = *first_read.get_or_insert(read_message.get_type_stamp());
+        assert_eq!(
+            read_message.get_type_stamp() - first_read,
+            ref_message.get_type_stamp() - messages[0].get_type_stamp()
+        );
     }
 
     let last = istream.get();
@@ -64,7 +70,7 @@ fn test_dataset() {
         return; // regular exit, everything went well
     }
 
-    if let Ok(_) = last {
+    if last.is_ok() {
         panic!("Got valid value when expecting EOF");
     }
 
@@ -0,0 +1,90 @@
+use crate::{AnySubscription, Channel, ConsumerConfig, Error, Message, Payload};
+use std::any::Any;
+use std::time::Instant;
+
+/// Object-safe view of a [`Channel`] that doesn't require knowing its payload type, for generic
+/// tools such as [`AnyRecorder`](crate::AnyRecorder). Payloads go through bincode, as in recordings.
+pub trait AnyChannel: Send + Sync {
+    /// [`Payload::format_name`] of the channel payload.
+    fn format_name(&self) -> &'static str;
+
+    /// Decodes `bytes` into a payload and pushes it.
+    fn push_bytes(&self, bytes: &[u8]) -> Result<(), Error>;
+
+    /// Like [`Channel::subscribe`], with encoded payloads.
+    fn subscribe_bytes(&self) -> AnySubscription;
+
+    /// Like [`AnyChannel::subscribe_bytes`], with the queue bounds of `config`.
+    fn subscribe_bytes_with(&self, config: &ConsumerConfig) -> AnySubscription;
+
+    /// For downcasting back to a `Channel<T>`.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Payload> AnyChannel for Channel<T> {
+    fn format_name(&self) -> &'static str {
+        T::format_name()
+    }
+
+    fn push_bytes(&self, bytes: &[u8]) -> Result<(), Error> {
+        let (payload, _): (T, usize) =
+            bincode::decode_from_slice(bytes, bincode::config::standard())?;
+        self.push_message(Message::new(Instant::now(), payload));
+        Ok(())
+    }
+
+    fn subscribe_bytes(&self) -> AnySubscription {
+        AnySubscription::new(self.new_consumer())
+    }
+
+    fn subscribe_bytes_with(&self, config: &ConsumerConfig) -> AnySubscription {
+        AnySubscription::new(self.new_consumer_with(config))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private::test_tools::{EmptyPayload, TestPayload};
+
+    #[test]
+    fn test_any_channel() {
+        let channel = Channel::<TestPayload>::new();
+        let any: &dyn AnyChannel = &channel;
+        assert_eq!(any.format_name(), TestPayload::format_name());
+
+        let mut typed = channel.subscribe();
+        let mut untyped = any.subscribe_bytes();
+
+        channel.push(TestPayload::new(42));
+        let encoded = untyped.try_pull();
+        assert_eq!(encoded.len(), 1);
+
+        any.push_bytes(encoded[0].get_bytes()).unwrap();
+        assert_eq!(untyped.try_pull().len(), 1);
+
+        let messages = typed.try_pull();
+        assert_eq!(messages.len(), 2);
+        messages.iter().for_each(|m| m.get_payload().check(42));
+
+        assert!(any.as_any().downcast_ref::<Channel<TestPayload>>() == Some(&channel));
+        assert!(
+            any.as_any()
+                .downcast_ref::<Channel<EmptyPayload>>()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_push_bad_bytes() {
+        let channel = Channel::<TestPayload>::new();
+        assert!(matches!(
+            channel.push_bytes(&[]),
+            Err(Error::BincodeDecode(_))
+        ));
+    }
+}
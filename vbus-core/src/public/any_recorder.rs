@@ -0,0 +1,161 @@
+use crate::private::io::RawOutputStream;
+use crate::public::any_subscription::AnyThreadedConsumer;
+use crate::{AnyChannel, ConsumerConfig, Error, ShutdownMode};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Records any [`AnyChannel`] to a file, in the same format as [`Recorder`](crate::Recorder).
+///
+/// Like a recorder, it is a threaded consumer: dropping it drains it by default. After a write
+/// error, e.g. a full disk, messages are no longer written and the error is kept for
+/// [`AnyRecorder::take_error`].
+pub struct AnyRecorder {
+    tc: Box<dyn AnyThreadedConsumer>,
+    error: Arc<Mutex<Option<Error>>>,
+}
+
+impl AnyRecorder {
+    pub fn new(channel: &dyn AnyChannel, path: &Path) -> Result<Self, Error> {
+        Self::new_with(channel, path, &ConsumerConfig::new())
+    }
+
+    pub fn new_with(
+        channel: &dyn AnyChannel,
+        path: &Path,
+        config: &ConsumerConfig,
+    ) -> Result<Self, Error> {
+        let file = Box::new(File::create(path)?);
+        Self::from_write(channel, file, config)
+    }
+
+    pub(crate) fn from_write(
+        channel: &dyn AnyChannel,
+        write: Box<dyn Write + Send>,
+        config: &ConsumerConfig,
+    ) -> Result<Self, Error> {
+        let mut ostream = RawOutputStream::new(write, channel.format_name())?;
+        let error = Arc::new(Mutex::new(None));
+        let error_for_thread = error.clone();
+        let mut failed = false;
+
+        // Acknowledged once returned, so Channel::flush() waits for the messages to be written
+        let process = Box::new(move |messages: Vec<crate::EncodedMessage>| {
+            for m in messages {
+                if failed {
+                    break;
+                }
+                let time_stamp = m.get_type_stamp();
+                if let Err(e) = ostream.append(m.into_bytes(), time_stamp) {
+                    *error_for_thread.lock().unwrap() = Some(e);
+                    failed = true;
+                }
+            }
+        });
+
+        let tc = channel.subscribe_bytes_with(config).into_threaded(
+            config,
+            config.on_drop.unwrap_or(ShutdownMode::Drain),
+            process,
+        );
+
+        Ok(Self { tc, error })
+    }
+
+    /// Number of messages discarded by the queue overflow policy.
+    pub fn dropped(&self) -> usize {
+        self.tc.dropped()
+    }
+
+    /// The write error that stopped the recording, if any.
+    pub fn take_error(&self) -> Option<Error> {
+        self.error.lock().unwrap().take()
+    }
+
+    /// See [`ThreadedConsumer::shutdown`](crate::ThreadedConsumer::shutdown).
+    pub fn shutdown(mut self, mode: ShutdownMode, timeout: Option<Duration>) -> usize {
+        self.tc.stop(mode, timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private::io::InputStream;
+    use crate::private::test_tools::{TempFile, TestPayload};
+    use crate::{Bus, Channel, Message, ThreadPool};
+
+    #[test]
+    fn test_any_recorder() {
+        let temp_file = TempFile::new().unwrap();
+        let bus = Bus::new();
+        let channel = bus.channel::<TestPayload>("test").unwrap();
+
+        let recorder = AnyRecorder::new(bus.any_channel("test").unwrap().as_ref(), &temp_file);
+        let recorder = recorder.unwrap();
+        (0..100).for_each(|x| channel.push(TestPayload::new(x)));
+        drop(recorder);
+
+        let file = File::open(temp_file.path()).unwrap();
+        let mut istream = InputStream::<TestPayload>::new(Box::new(file)).unwrap();
+        let messages = (0..100)
+            .map(|_| istream.get().unwrap())
+            .collect::<Vec<Message<TestPayload>>>();
+
+        messages
+            .iter()
+            .enumerate()
+            .for_each(|(i, m)| m.get_payload().check(i));
+        assert!(matches!(istream.get(), Err(Error::RegularEof)));
+    }
+
+    #[test]
+    fn test_any_recorder_flush() {
+        let temp_file = TempFile::new().unwrap();
+        let channel = Channel::<TestPayload>::new();
+        let pool = ThreadPool::new(1);
+        let config = ConsumerConfig::new().pool(&pool);
+        let recorder = AnyRecorder::new_with(&channel, &temp_file, &config).unwrap();
+
+        (0..10).for_each(|x| channel.push(TestPayload::new(x)));
+        channel.flush(); // the messages are in the file
+
+        let file = File::open(temp_file.path()).unwrap();
+        let mut istream = InputStream::<TestPayload>::new(Box::new(file)).unwrap();
+        (0..10).for_each(|i| istream.get().unwrap().get_payload().check(i));
+
+        assert_eq!(recorder.shutdown(ShutdownMode::Immediate, None), 0);
+    }
+
+    // Accepts the stream header and format name, then fails like a full disk
+    struct FullDisk(usize);
+
+    impl Write for FullDisk {
+        fn write(&mut self, buffer: &[u8]) -> std::io::Result<usize> {
+            if self.0 < buffer.len() {
+                return Err(std::io::Error::from(std::io::ErrorKind::StorageFull));
+            }
+            self.0 -= buffer.len();
+            Ok(buffer.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_any_recorder_write_error() {
+        let channel = Channel::<TestPayload>::new();
+        let write = Box::new(FullDisk(1024));
+        let recorder = AnyRecorder::from_write(&channel, write, &ConsumerConfig::new()).unwrap();
+
+        (0..100).for_each(|x| channel.push(TestPayload::new(x)));
+        channel.flush();
+
+        assert!(matches!(recorder.take_error(), Some(Error::StdIo(_))));
+        drop(recorder); // must not panic
+    }
+}
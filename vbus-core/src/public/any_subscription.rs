@@ -0,0 +1,160 @@
+use crate::private::Consumer;
+use crate::private::supervisor::Supervisor;
+use crate::{
+    ConsumerConfig, EncodedMessage, Message, Payload, SelectHandle, Selectable, ShutdownMode,
+    ThreadedConsumer,
+};
+use std::time::{Duration, Instant};
+
+/// Pull-based reader of an [`AnyChannel`](crate::AnyChannel), created by
+/// [`AnyChannel::subscribe_bytes`](crate::AnyChannel::subscribe_bytes).
+pub struct AnySubscription {
+    consumer: Box<dyn ErasedConsumer>,
+}
+
+impl AnySubscription {
+    pub(crate) fn new<T: Payload>(consumer: Consumer<T>) -> Self {
+        Self {
+            consumer: Box::new(consumer),
+        }
+    }
+
+    pub fn format_name(&self) -> &'static str {
+        self.consumer.format_name()
+    }
+
+    /// Returns all queued messages without blocking, possibly none.
+    pub fn try_pull(&mut self) -> Vec<EncodedMessage> {
+        self.consumer.pull()
+    }
+
+    /// Blocks until at least one message is available, then returns all queued messages.
+    pub fn wait_pull(&mut self) -> Vec<EncodedMessage> {
+        loop {
+            let messages = self.consumer.wait_pull(None);
+            if !messages.is_empty() {
+                return messages;
+            }
+        }
+    }
+
+    /// Like [`AnySubscription::wait_pull`], but returns an empty vector after `timeout`.
+    pub fn wait_pull_timeout(&mut self, timeout: Duration) -> Vec<EncodedMessage> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let messages = self.consumer.wait_pull(Some(deadline));
+            if !messages.is_empty() || Instant::now() >= deadline {
+                return messages;
+            }
+        }
+    }
+
+    /// Number of messages discarded by the queue overflow policy.
+    pub fn dropped(&self) -> usize {
+        self.consumer.dropped()
+    }
+
+    // Hands the subscription over to a threaded consumer, as Channel::new_threaded_consumer_with
+    pub(crate) fn into_threaded(
+        self,
+        config: &ConsumerConfig,
+        on_drop: ShutdownMode,
+        process: Box<dyn FnMut(Vec<EncodedMessage>) + Send>,
+    ) -> Box<dyn AnyThreadedConsumer> {
+        self.consumer.into_threaded(config, on_drop, process)
+    }
+}
+
+// ThreadedConsumer, without its payload type
+pub(crate) trait AnyThreadedConsumer: Send {
+    fn dropped(&self) -> usize;
+    fn stop(&mut self, mode: ShutdownMode, timeout: Option<Duration>) -> usize;
+}
+
+impl<T: Payload> AnyThreadedConsumer for ThreadedConsumer<T> {
+    fn dropped(&self) -> usize {
+        ThreadedConsumer::dropped(self)
+    }
+
+    fn stop(&mut self, mode: ShutdownMode, timeout: Option<Duration>) -> usize {
+        ThreadedConsumer::stop(self, mode, timeout)
+    }
+}
+
+impl Selectable for AnySubscription {
+    fn select_handle(&self) -> SelectHandle {
+        self.consumer.select_handle()
+    }
+}
+
+trait ErasedConsumer: Send + Sync {
+    fn format_name(&self) -> &'static str;
+    fn pull(&self) -> Vec<EncodedMessage>;
+    fn wait_pull(&self, deadline: Option<Instant>) -> Vec<EncodedMessage>;
+    fn dropped(&self) -> usize;
+    fn select_handle(&self) -> SelectHandle;
+    fn into_threaded(
+        self: Box<Self>,
+        config: &ConsumerConfig,
+        on_drop: ShutdownMode,
+        process: Box<dyn FnMut(Vec<EncodedMessage>) + Send>,
+    ) -> Box<dyn AnyThreadedConsumer>;
+}
+
+impl<T: Payload> ErasedConsumer for Consumer<T> {
+    fn format_name(&self) -> &'static str {
+        T::format_name()
+    }
+
+    fn pull(&self) -> Vec<EncodedMessage> {
+        encode(Consumer::pull(self))
+    }
+
+    fn wait_pull(&self, deadline: Option<Instant>) -> Vec<EncodedMessage> {
+        encode(match deadline {
+            None => Consumer::wait_pull(self),
+            Some(deadline) => {
+                self.wait_pull_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+        })
+    }
+
+    fn dropped(&self) -> usize {
+        self.queue().dropped()
+    }
+
+    fn select_handle(&self) -> SelectHandle {
+        SelectHandle::new(self.queue())
+    }
+
+    fn into_threaded(
+        self: Box<Self>,
+        config: &ConsumerConfig,
+        on_drop: ShutdownMode,
+        mut process: Box<dyn FnMut(Vec<EncodedMessage>) + Send>,
+    ) -> Box<dyn AnyThreadedConsumer> {
+        let supervisor = Supervisor::new(config.supervision, move |messages| {
+            process(encode(messages))
+        })
+        .on_panic(config.on_panic.clone());
+
+        Box::new(ThreadedConsumer::new_on(
+            *self,
+            supervisor,
+            config.pool.as_ref(),
+            on_drop,
+        ))
+    }
+}
+
+fn encode<T: Payload>(messages: Vec<Message<T>>) -> Vec<EncodedMessage> {
+    messages
+        .iter()
+        .map(|m| {
+            // Encoding to memory only fails if the payload Encode implementation does
+            let bytes = bincode::encode_to_vec(m.get_payload(), bincode::config::standard())
+                .expect("payload encoding failed");
+            EncodedMessage::new(m.get_type_stamp(), bytes)
+        })
+        .collect()
+}
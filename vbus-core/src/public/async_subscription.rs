@@ -0,0 +1,168 @@
+use crate::private::Consumer;
+use crate::private::queue::Hook;
+use crate::{Message, Payload};
+use futures::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Asynchronous reader of a [`Channel`](crate::Channel), created by
+/// [`Channel::subscribe_async`](crate::Channel::subscribe_async).
+///
+/// The stream never ends: it yields every message pushed after the subscription was created, and
+/// the task is woken up by the pusher.
+pub struct AsyncSubscription<T: Payload> {
+    consumer: Consumer<T>,
+    pending: VecDeque<Message<T>>,
+    task_waker: Arc<Mutex<Option<Waker>>>,
+    hook: Hook,
+}
+
+impl<T: Payload> AsyncSubscription<T> {
+    pub(crate) fn new(consumer: Consumer<T>) -> Self {
+        let task_waker = Arc::new(Mutex::new(None::<Waker>));
+        let task_waker_for_hook = task_waker.clone();
+        let hook: Hook = Arc::new(move || {
+            if let Some(waker) = task_waker_for_hook.lock().unwrap().take() {
+                waker.wake();
+            }
+        });
+
+        consumer.queue().add_hook(&hook);
+
+        Self {
+            consumer,
+            pending: VecDeque::new(),
+            task_waker,
+            hook,
+        }
+    }
+
+    /// Number of messages discarded by the queue overflow policy.
+    pub fn dropped(&self) -> usize {
+        self.consumer.queue().dropped()
+    }
+
+    fn pull_pending(&mut self) -> Option<Message<T>> {
+        if self.pending.is_empty() {
+            self.pending.extend(self.consumer.pull());
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+impl<T: Payload> Stream for AsyncSubscription<T> {
+    type Item = Message<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Some(message) = this.pull_pending() {
+            return Poll::Ready(Some(message));
+        }
+
+        *this.task_waker.lock().unwrap() = Some(cx.waker().clone());
+
+        // A push may have happened before the waker was registered
+        match this.pull_pending() {
+            Some(message) => Poll::Ready(Some(message)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T: Payload> Drop for AsyncSubscription<T> {
+    fn drop(&mut self) {
+        self.consumer.queue().remove_hook(&self.hook);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::private::test_tools::TestPayload;
+    use crate::{Channel, OverflowPolicy};
+    use futures::executor::{LocalPool, block_on};
+    use futures::task::LocalSpawnExt;
+    use futures::{SinkExt, StreamExt};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stream() {
+        let channel = Channel::<TestPayload>::new();
+        let subscription = channel.subscribe_async();
+
+        (0..5).for_each(|x| channel.push(TestPayload::new(x)));
+
+        let values: Vec<usize> = block_on(
+            subscription
+                .take(5)
+                .map(|m| m.get_payload().value())
+                .collect(),
+        );
+        assert_eq!(values, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_stream_wakes_up() {
+        let channel = Channel::<TestPayload>::new();
+        let subscription = channel.subscribe_async();
+
+        let channel_for_thread = channel.clone();
+        let join_handle = std::thread::spawn(move || {
+            for i in 0..10 {
+                sleep(Duration::from_millis(10));
+                channel_for_thread.push(TestPayload::new(i));
+            }
+        });
+
+        let values: Vec<usize> = block_on(
+            subscription
+                .take(10)
+                .map(|m| m.get_payload().value())
+                .collect(),
+        );
+        assert_eq!(values, (0..10).collect::<Vec<_>>());
+
+        join_handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_sink_to_stream() {
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe_async_bounded(10, OverflowPolicy::DropOldest);
+        let mut sink = channel.sink();
+
+        let mut pool = LocalPool::new();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received_for_task = received.clone();
+
+        pool.spawner()
+            .spawn_local(async move {
+                while let Some(message) = subscription.next().await {
+                    received_for_task
+                        .borrow_mut()
+                        .push(message.get_payload().value());
+                    if message.get_payload().value() == 2 {
+                        return;
+                    }
+                }
+            })
+            .unwrap();
+
+        pool.spawner()
+            .spawn_local(async move {
+                for i in 0..3 {
+                    sink.send(TestPayload::new(i)).await.unwrap();
+                }
+            })
+            .unwrap();
+
+        pool.run();
+        assert_eq!(received.borrow().as_slice(), [0, 1, 2]);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{AnyChannel, Channel, Error, Payload};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Registry of channels by name, so that components can find each other without being handed
+/// every channel they use. Clones share the same registry.
+#[derive(Clone, Default)]
+pub struct Bus {
+    entries: Arc<Mutex<BTreeMap<String, Arc<dyn AnyChannel>>>>,
+}
+
+impl Bus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the channel registered as `name`, creating it if needed.
+    /// Fails with [`Error::TypeMismatch`] if it was registered with another payload type.
+    pub fn channel<T: Payload>(&self, name: &str) -> Result<Channel<T>, Error> {
+        let mut entries = self.entries.lock().unwrap();
+
+        let channel = entries
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(Channel::<T>::new()));
+
+        Self::downcast(name, channel.as_ref())
+    }
+
+    /// Like [`Bus::channel`], but returns `None` rather than creating the channel.
+    pub fn get<T: Payload>(&self, name: &str) -> Result<Option<Channel<T>>, Error> {
+        let entries = self.entries.lock().unwrap();
+
+        entries
+            .get(name)
+            .map(|channel| Self::downcast(name, channel.as_ref()))
+            .transpose()
+    }
+
+    /// Untyped access to the channel registered as `name`, if any.
+    pub fn any_channel(&self, name: &str) -> Option<Arc<dyn AnyChannel>> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(name).cloned()
+    }
+
+    /// Names and payload format names of the registered channels, sorted by name.
+    pub fn channels(&self) -> Vec<(String, &'static str)> {
+        let entries = self.entries.lock().unwrap();
+
+        entries
+            .iter()
+            .map(|(name, channel)| (name.clone(), channel.format_name()))
+            .collect()
+    }
+
+    fn downcast<T: Payload>(name: &str, channel: &dyn AnyChannel) -> Result<Channel<T>, Error> {
+        // Format names may collide, the type check is what really matters
+        match channel.as_any().downcast_ref::<Channel<T>>() {
+            Some(typed) if channel.format_name() == T::format_name() => Ok(typed.clone()),
+            _ => Err(Error::TypeMismatch {
+                channel: name.to_string(),
+                registered: channel.format_name().to_string(),
+                requested: T::format_name().to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private::test_tools::{EmptyPayload, TestPayload};
+
+    #[test]
+    fn test_bus() {
+        let bus = Bus::new();
+
+        let a = bus.channel::<TestPayload>("a").unwrap();
+        assert!(bus.channel::<TestPayload>("a").unwrap() == a);
+        assert!(bus.clone().get::<TestPayload>("a").unwrap().unwrap() == a);
+        assert!(bus.get::<TestPayload>("b").unwrap().is_none());
+
+        let b = bus.channel::<EmptyPayload>("b").unwrap();
+        assert!(bus.get::<EmptyPayload>("b").unwrap().unwrap() == b);
+
+        let any_b = bus.any_channel("b").unwrap();
+        assert_eq!(any_b.format_name(), EmptyPayload::format_name());
+        assert!(bus.any_channel("c").is_none());
+
+        assert_eq!(
+            bus.channels(),
+            [
+                ("a".to_string(), TestPayload::format_name()),
+                ("b".to_string(), EmptyPayload::format_name())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bus_type_mismatch() {
+        let bus = Bus::new();
+        bus.channel::<TestPayload>("a").unwrap();
+
+        match bus.channel::<EmptyPayload>("a") {
+            Err(Error::TypeMismatch {
+                channel,
+                registered,
+                requested,
+            }) => {
+                assert_eq!(channel, "a");
+                assert_eq!(registered, TestPayload::format_name());
+                assert_eq!(requested, EmptyPayload::format_name());
+            }
+            _ => panic!("expected a type mismatch"),
+        }
+
+        assert!(matches!(
+            bus.get::<EmptyPayload>("a"),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+}
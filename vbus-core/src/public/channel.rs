@@ -1,14 +1,33 @@
+#[cfg(feature = "async")]
+use crate::AsyncSubscription;
+#[cfg(feature = "async")]
+use crate::ChannelSink;
+use crate::ChannelStats;
+use crate::ConsumerConfig;
 use crate::Error;
+use crate::FdSubscription;
+use crate::Latch;
+use crate::LatestSubscription;
 use crate::Message;
+use crate::OverflowPolicy;
 use crate::Payload;
 use crate::Player;
 use crate::Recorder;
+use crate::ShutdownMode;
+use crate::Subscription;
+use crate::Supervision;
 use crate::ThreadedConsumer;
 use crate::private::Consumer;
+use crate::private::history::History;
 use crate::private::queue::Queue;
+use crate::private::rate::RateMeter;
+use crate::private::ring::Ring;
+use crate::private::supervisor::Supervisor;
+use std::any::Any;
 use std::path::Path;
-use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak};
+use std::time::{Duration, Instant};
 
 pub struct Channel<T: Payload> {
     data: Arc<ChannelData<T>>,
@@ -32,11 +51,7 @@ impl<T: Payload> Clone for Channel<T> {
 
 impl<T: Payload> Default for Channel<T> {
     fn default() -> Self {
-        Self {
-            data: Arc::new(ChannelData {
-                queues: RwLock::new(Vec::<Queue<T>>::new()),
-            }),
-        }
+        Self::with_history(None)
     }
 }
 
@@ -45,6 +60,29 @@ impl<T: Payload> Channel<T> {
         Self::default()
     }
 
+    /// Creates a channel that keeps recent messages, as described by `latch`, and replays them to
+    /// every new subscriber before any newer message.
+    pub fn new_latched(latch: Latch) -> Self {
+        Self::with_history(Some(latch))
+    }
+
+    fn with_history(latch: Option<Latch>) -> Self {
+        let ring = Ring::new();
+        let history = latch.map(|latch| Mutex::new(History::new(latch, &ring)));
+
+        Self {
+            data: Arc::new(ChannelData {
+                queues: RwLock::new(Vec::<Queue<T>>::new()),
+                ring,
+                history,
+                rate: RateMeter::new(),
+                taps: RwLock::new(Vec::new()),
+                tapped: AtomicBool::new(false),
+                upstream: None,
+            }),
+        }
+    }
+
     pub fn push(&self, payload: T) {
         self.push_message(Message::new(Instant::now(), payload));
     }
@@ -53,10 +91,108 @@ impl<T: Payload> Channel<T> {
         self.broadcast(message);
     }
 
+    /// Blocks until every current subscriber has taken and processed all the messages pushed
+    /// before the call. Threaded consumers and recorders are done once their `process` call
+    /// returns, other subscriptions once the messages are pulled.
+    ///
+    /// Must not be called from a consumer of this channel, which would wait for itself.
+    pub fn flush(&self) {
+        self.flush_until(None);
+    }
+
+    /// Like [`Channel::flush`], but gives up after `timeout`. Returns false if it did.
+    pub fn flush_timeout(&self, timeout: Duration) -> bool {
+        self.flush_until(Some(Instant::now() + timeout))
+    }
+
+    /// Snapshot of the channel activity and of how far behind each subscriber is.
+    pub fn stats(&self) -> ChannelStats {
+        let consumers = self
+            .queues_read()
+            .iter()
+            .map(|queue| queue.stats())
+            .collect::<Vec<_>>();
+
+        ChannelStats {
+            pushed: self.data.ring.written(),
+            rate: self.data.rate.rate(Instant::now()),
+            subscribers: consumers.len(),
+            consumers,
+        }
+    }
+
+    fn flush_until(&self, deadline: Option<Instant>) -> bool {
+        let targets = self
+            .queues_read()
+            .iter()
+            .map(|queue| (queue.clone(), queue.flush_target()))
+            .collect::<Vec<_>>();
+
+        targets
+            .iter()
+            .all(|(queue, target)| queue.wait_acked(*target, deadline))
+    }
+
     pub(crate) fn new_consumer(&self) -> Consumer<T> {
         Consumer::new(self)
     }
 
+    pub(crate) fn new_bounded_consumer(
+        &self,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Consumer<T> {
+        Consumer::new_bounded(self, capacity, policy)
+    }
+
+    pub fn subscribe(&self) -> Subscription<T> {
+        Subscription::new(self.new_consumer())
+    }
+
+    /// Like [`Channel::subscribe`], but the subscription queue holds at most `capacity` messages
+    /// and applies `policy` when full. Panics if `capacity` is 0.
+    pub fn subscribe_bounded(&self, capacity: usize, policy: OverflowPolicy) -> Subscription<T> {
+        Subscription::new(self.new_bounded_consumer(capacity, policy))
+    }
+
+    /// Subscribes in latest-value mode: only the most recent message is kept.
+    pub fn subscribe_latest(&self) -> LatestSubscription<T> {
+        LatestSubscription::new(self.new_bounded_consumer(1, OverflowPolicy::DropOldest))
+    }
+
+    /// Subscribes through a file descriptor that is readable while messages are pending. Fails if
+    /// the pipe behind it can't be created, e.g. when the process is out of file descriptors.
+    pub fn subscribe_fd(&self) -> Result<FdSubscription<T>, Error> {
+        FdSubscription::new(self.new_consumer())
+    }
+
+    #[cfg(feature = "async")]
+    pub fn subscribe_async(&self) -> AsyncSubscription<T> {
+        AsyncSubscription::new(self.new_consumer())
+    }
+
+    /// Like [`Channel::subscribe_async`], with a bounded queue. Panics if `capacity` is 0.
+    #[cfg(feature = "async")]
+    pub fn subscribe_async_bounded(
+        &self,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> AsyncSubscription<T> {
+        AsyncSubscription::new(self.new_bounded_consumer(capacity, policy))
+    }
+
+    #[cfg(feature = "async")]
+    pub fn sink(&self) -> ChannelSink<T> {
+        ChannelSink::new(self)
+    }
+
+    pub(crate) fn new_consumer_with(&self, config: &ConsumerConfig) -> Consumer<T> {
+        match config.bounds {
+            Some((capacity, policy)) => self.new_bounded_consumer(capacity, policy),
+            None => self.new_consumer(),
+        }
+    }
+
     pub fn new_threaded_consumer(
         &self,
         process: impl FnMut(Vec<Message<T>>) + Send + 'static,
@@ -65,18 +201,257 @@ impl<T: Payload> Channel<T> {
         ThreadedConsumer::new(consumer, process)
     }
 
+    pub fn new_threaded_consumer_with(
+        &self,
+        config: &ConsumerConfig,
+        process: impl FnMut(Vec<Message<T>>) + Send + 'static,
+    ) -> ThreadedConsumer<T> {
+        let consumer = self.new_consumer_with(config);
+        let supervisor =
+            Supervisor::new(config.supervision, process).on_panic(config.on_panic.clone());
+        ThreadedConsumer::new_on(
+            consumer,
+            supervisor,
+            config.pool.as_ref(),
+            config.on_drop.unwrap_or(ShutdownMode::Immediate),
+        )
+    }
+
+    /// Like [`Channel::new_threaded_consumer_with`], but the closure is built by `make_process`,
+    /// which is called again after a panic when supervision is [`Supervision::Restart`].
+    pub fn new_supervised_consumer<P>(
+        &self,
+        config: &ConsumerConfig,
+        make_process: impl FnMut() -> P + Send + 'static,
+    ) -> ThreadedConsumer<T>
+    where
+        P: FnMut(Vec<Message<T>>) + Send + 'static,
+    {
+        let consumer = self.new_consumer_with(config);
+        let supervisor = Supervisor::new_restartable(config.supervision, make_process)
+            .on_panic(config.on_panic.clone());
+        ThreadedConsumer::new_on(
+            consumer,
+            supervisor,
+            config.pool.as_ref(),
+            config.on_drop.unwrap_or(ShutdownMode::Immediate),
+        )
+    }
+
+    /// Like [`Channel::new_threaded_consumer`], but the consumer queue holds at most `capacity`
+    /// messages and applies `policy` when full. Panics if `capacity` is 0.
+    pub fn new_bounded_threaded_consumer(
+        &self,
+        capacity: usize,
+        policy: OverflowPolicy,
+        process: impl FnMut(Vec<Message<T>>) + Send + 'static,
+    ) -> ThreadedConsumer<T> {
+        let consumer = self.new_bounded_consumer(capacity, policy);
+        ThreadedConsumer::new(consumer, process)
+    }
+
     pub fn new_recorder(&self, path: &Path) -> Result<Recorder<T>, Error> {
-        Recorder::<T>::new(self, path)
+        Recorder::<T>::new(
+            self.new_consumer(),
+            None,
+            Supervision::default(),
+            None,
+            ShutdownMode::Drain,
+            path,
+        )
+    }
+
+    pub fn new_recorder_with(
+        &self,
+        path: &Path,
+        config: &ConsumerConfig,
+    ) -> Result<Recorder<T>, Error> {
+        Recorder::<T>::new(
+            self.new_consumer_with(config),
+            config.pool.as_ref(),
+            config.supervision,
+            config.on_panic.clone(),
+            config.on_drop.unwrap_or(ShutdownMode::Drain),
+            path,
+        )
+    }
+
+    /// Like [`Channel::new_recorder`], with a bounded queue in front of the file.
+    /// Panics if `capacity` is 0.
+    pub fn new_bounded_recorder(
+        &self,
+        path: &Path,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<Recorder<T>, Error> {
+        Recorder::<T>::new(
+            self.new_bounded_consumer(capacity, policy),
+            None,
+            Supervision::default(),
+            None,
+            ShutdownMode::Drain,
+            path,
+        )
     }
 
     pub fn new_player(&self, path: &Path) -> Result<Player<T>, Error> {
         Player::<T>::new(self, path)
     }
 
+    /// Returns a channel fed with `f` applied to every message pushed to this one from now on,
+    /// with the same time stamps.
+    ///
+    /// `f` runs on the pushing thread. Forwarding only runs while the derived channel has
+    /// subscribers: it's torn down when the last one goes away, and set up again by the next one.
+    pub fn map<B: Payload>(&self, f: impl Fn(&T) -> B + Send + Sync + 'static) -> Channel<B> {
+        self.derive(move |message| {
+            Some(Message::new(
+                message.get_type_stamp(),
+                f(message.get_payload()),
+            ))
+        })
+    }
+
+    /// Like [`Channel::map`], forwarding the messages for which `f` returns true.
+    pub fn filter(&self, f: impl Fn(&T) -> bool + Send + Sync + 'static) -> Channel<T> {
+        self.derive(move |message| f(message.get_payload()).then(|| message.clone()))
+    }
+
+    /// Like [`Channel::map`], forwarding the payloads for which `f` returns something.
+    pub fn filter_map<B: Payload>(
+        &self,
+        f: impl Fn(&T) -> Option<B> + Send + Sync + 'static,
+    ) -> Channel<B> {
+        self.derive(move |message| {
+            f(message.get_payload()).map(|payload| Message::new(message.get_type_stamp(), payload))
+        })
+    }
+
+    fn derive<B: Payload>(
+        &self,
+        forward: impl Fn(&Message<T>) -> Option<Message<B>> + Send + Sync + 'static,
+    ) -> Channel<B> {
+        let source = self.clone();
+        let forward = Arc::new(forward);
+
+        let connect = move |derived: &Channel<B>| -> Connection {
+            let weak: Weak<ChannelData<B>> = Arc::downgrade(&derived.data);
+            let forward = forward.clone();
+
+            let tap: Tap<T> = Arc::new(move |message| {
+                let Some(data) = weak.upgrade() else {
+                    return false;
+                };
+
+                if let Some(message) = forward(message) {
+                    Channel { data }.push_message(message);
+                }
+                true
+            });
+
+            source.add_tap(tap.clone());
+            Box::new(TapGuard {
+                source: source.clone(),
+                tap,
+            })
+        };
+
+        let mut derived = Channel::<B>::new();
+        Arc::get_mut(&mut derived.data).unwrap().upstream = Some(Upstream {
+            connect: Box::new(connect),
+            connection: Mutex::new(None),
+        });
+        derived
+    }
+
+    // Connects a derived channel to its source while it has readers, and only then
+    fn update_upstream(&self) {
+        let Some(upstream) = &self.data.upstream else {
+            return;
+        };
+
+        let mut connection = upstream.connection.lock().unwrap();
+        let has_readers = !self.queues_read().is_empty() || self.is_tapped();
+        match (has_readers, connection.is_some()) {
+            (true, false) => *connection = Some((upstream.connect)(self)),
+            (false, true) => *connection = None, // removes the tap
+            _ => {}
+        }
+    }
+
+    fn add_tap(&self, tap: Tap<T>) {
+        {
+            let mut taps = self.data.taps.write().unwrap();
+            taps.push(tap);
+            self.data.tapped.store(true, Ordering::SeqCst);
+        }
+
+        self.update_upstream();
+    }
+
+    fn remove_taps(&self, removed: &[Tap<T>]) {
+        {
+            let mut taps = self.data.taps.write().unwrap();
+            taps.retain(|tap| !removed.iter().any(|r| Arc::ptr_eq(tap, r)));
+            self.data.tapped.store(!taps.is_empty(), Ordering::SeqCst);
+        }
+
+        self.update_upstream();
+    }
+
+    fn is_tapped(&self) -> bool {
+        self.data.tapped.load(Ordering::SeqCst)
+    }
+
+    fn run_taps(&self, message: &Message<T>) {
+        if !self.is_tapped() {
+            return;
+        }
+
+        // Called outside the lock: a tap may push to, or derive from, this channel again
+        let taps = self.data.taps.read().unwrap().clone();
+        let done = taps
+            .into_iter()
+            .filter(|tap| !tap(message))
+            .collect::<Vec<_>>();
+
+        if !done.is_empty() {
+            self.remove_taps(&done);
+        }
+    }
+
     fn broadcast(&self, message: Message<T>) {
-        for queue in self.queues_read().iter() {
-            queue.push(message.clone());
+        let full = {
+            // Written under the queues lock, so that add_queue() sees either all or none of the push
+            let queues = self.queues_read();
+
+            match &self.data.history {
+                Some(history) => {
+                    let mut history = history.lock().unwrap();
+                    self.data.ring.push(message.clone());
+                    history.record();
+                }
+                None => self.data.ring.push(message.clone()),
+            }
+
+            // Not the message time stamp, which may be in the past (e.g. replayed messages)
+            self.data.rate.record(Instant::now());
+
+            // O(subscribers): unbounded queues only need a wake-up if waiting, bounded ones a copy
+            queues
+                .iter()
+                .filter(|queue| !queue.try_push(&message))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        // Full queues with the Block policy are waited for without holding the channel, which
+        // their reader may need, e.g. to subscribe
+        for queue in full {
+            queue.push(&message);
         }
+
+        self.run_taps(&message);
     }
 
     fn queues_read(&self) -> RwLockReadGuard<'_, Vec<Queue<T>>> {
@@ -88,14 +463,26 @@ impl<T: Payload> Channel<T> {
     }
 
     pub(crate) fn add_queue(&self, queue: &Queue<T>) {
-        self.queues_write().push(queue.clone());
+        {
+            let mut queues = self.queues_write();
+
+            let start = match &self.data.history {
+                Some(history) => history.lock().unwrap().replay(),
+                None => self.data.ring.cursor(),
+            };
+            queue.attach(start);
+
+            queues.push(queue.clone());
+        }
+
+        self.update_upstream();
     }
 
     pub(crate) fn remove_queue(&self, queue: &Queue<T>) {
         self.queues_write().retain(|q| q != queue);
+        self.update_upstream();
     }
 
-    #[cfg(test)]
     pub(crate) fn queues_len(&self) -> usize {
         self.queues_read().len()
     }
@@ -103,6 +490,38 @@ impl<T: Payload> Channel<T> {
 
 struct ChannelData<T: Payload> {
     queues: RwLock<Vec<Queue<T>>>,
+    ring: Ring<T>,
+    history: Option<Mutex<History<T>>>,
+    rate: RateMeter,
+    taps: RwLock<Vec<Tap<T>>>,
+    tapped: AtomicBool,            // avoids taking the taps lock on every push
+    upstream: Option<Upstream<T>>, // of a derived channel
+}
+
+// Called by the pushing thread after every push, returns false once it's no longer needed
+type Tap<T> = Arc<dyn Fn(&Message<T>) -> bool + Send + Sync>;
+
+// Keeps a tap on the source of a derived channel until dropped
+type Connection = Box<dyn Any + Send + Sync>;
+
+// Taps the source of a derived channel, given the derived channel
+type Connect<T> = Box<dyn Fn(&Channel<T>) -> Connection + Send + Sync>;
+
+// Source of a derived channel, tapped while the derived channel has readers
+struct Upstream<T: Payload> {
+    connect: Connect<T>,
+    connection: Mutex<Option<Connection>>,
+}
+
+struct TapGuard<T: Payload> {
+    source: Channel<T>,
+    tap: Tap<T>,
+}
+
+impl<T: Payload> Drop for TapGuard<T> {
+    fn drop(&mut self) {
+        self.source.remove_taps(std::slice::from_ref(&self.tap));
+    }
 }
 
 #[cfg(test)]
@@ -110,6 +529,179 @@ mod tests {
     use super::*;
     use crate::private::test_tools::{EmptyPayload, TestPayload};
 
+    #[test]
+    fn test_flush() {
+        let channel = Channel::<TestPayload>::new();
+        let processed = Arc::new(Mutex::new(0usize));
+        let processed_for_thread = processed.clone();
+
+        let _tc = channel.new_threaded_consumer(move |input| {
+            std::thread::sleep(Duration::from_millis(50));
+            *processed_for_thread.lock().unwrap() += input.len();
+        });
+        let mut subscription = channel.subscribe();
+
+        (0..10).for_each(|x| channel.push(TestPayload::new(x)));
+        assert!(!channel.flush_timeout(Duration::from_millis(10))); // nothing pulled yet
+
+        assert_eq!(subscription.try_pull().len(), 10);
+        assert!(channel.flush_timeout(Duration::from_secs(5)));
+        assert_eq!(*processed.lock().unwrap(), 10);
+
+        drop(subscription);
+        channel.push(TestPayload::new(10));
+        channel.flush();
+        assert_eq!(*processed.lock().unwrap(), 11);
+    }
+
+    #[test]
+    fn test_stats() {
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe();
+        let _bounded = channel.subscribe_bounded(2, OverflowPolicy::DropOldest);
+
+        (0..5).for_each(|x| channel.push(TestPayload::new(x)));
+        subscription.try_pull();
+
+        let stats = channel.stats();
+        assert_eq!(stats.pushed, 5);
+        assert_eq!(stats.rate, 5.0);
+        assert_eq!(stats.subscribers, 2);
+
+        assert_eq!(stats.consumers[0].depth, 0);
+        assert_eq!(stats.consumers[0].high_water_mark, 5);
+        assert!(stats.consumers[0].since_last_pull.is_some());
+
+        assert_eq!(stats.consumers[1].depth, 2);
+        assert_eq!(stats.consumers[1].dropped, 3);
+        assert!(stats.consumers[1].since_last_pull.is_none());
+    }
+
+    #[test]
+    fn test_blocked_push_releases_channel() {
+        let channel = Channel::<TestPayload>::new();
+        let channel_for_consumer = channel.clone();
+        let (sender, received) = std::sync::mpsc::channel();
+
+        let _tc =
+            channel.new_bounded_threaded_consumer(1, OverflowPolicy::Block, move |messages| {
+                std::thread::sleep(Duration::from_millis(20)); // the pusher blocks meanwhile
+                let _subscription = channel_for_consumer.subscribe();
+                messages
+                    .iter()
+                    .for_each(|m| sender.send(m.get_payload().value()).unwrap());
+            });
+
+        (0..5).for_each(|x| channel.push(TestPayload::new(x)));
+        let values = (0..5)
+            .map(|_| received.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(values, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_map_filter() {
+        let channel = Channel::<TestPayload>::new();
+        let doubled = channel.map(|p| TestPayload::new(p.value() * 2));
+        let even = channel.filter(|p| p.value() % 2 == 0);
+        let halved =
+            channel.filter_map(|p| (p.value() % 2 == 0).then(|| TestPayload::new(p.value() / 2)));
+
+        let mut source = channel.subscribe();
+        let mut doubled = doubled.subscribe();
+        let mut even = even.subscribe();
+        let mut halved = halved.subscribe();
+
+        (0..10).for_each(|x| channel.push(TestPayload::new(x)));
+        let source = source.try_pull();
+
+        let values = |messages: &[Message<TestPayload>]| {
+            messages
+                .iter()
+                .map(|m| m.get_payload().value())
+                .collect::<Vec<_>>()
+        };
+        let doubled = doubled.try_pull();
+        assert_eq!(values(&doubled), [0, 2, 4, 6, 8, 10, 12, 14, 16, 18]);
+        assert_eq!(values(&even.try_pull()), [0, 2, 4, 6, 8]);
+        assert_eq!(values(&halved.try_pull()), [0, 1, 2, 3, 4]);
+
+        for (s, d) in source.iter().zip(doubled.iter()) {
+            assert_eq!(s.get_type_stamp(), d.get_type_stamp());
+        }
+    }
+
+    #[test]
+    fn test_derived_teardown() {
+        let channel = Channel::<TestPayload>::new();
+        let calls = Arc::new(Mutex::new(0usize));
+        let calls_for_map = calls.clone();
+
+        let derived = channel.map(move |p| {
+            *calls_for_map.lock().unwrap() += 1;
+            TestPayload::new(p.value())
+        });
+        channel.push(TestPayload::new(0)); // nobody's listening
+        assert_eq!(*calls.lock().unwrap(), 0);
+
+        let chained = derived.filter(|_| true);
+        let subscription = chained.subscribe();
+        channel.push(TestPayload::new(1));
+        assert_eq!(*calls.lock().unwrap(), 1);
+
+        drop(derived);
+        channel.push(TestPayload::new(2)); // still alive through the chained channel
+        assert_eq!(*calls.lock().unwrap(), 2);
+
+        drop(chained);
+        drop(subscription);
+        channel.push(TestPayload::new(3));
+        assert_eq!(*calls.lock().unwrap(), 2);
+        assert!(!channel.is_tapped());
+    }
+
+    #[test]
+    fn test_derived_resubscribe() {
+        let channel = Channel::<TestPayload>::new();
+        let derived = channel.map(|p| TestPayload::new(p.value()));
+        let chained = derived.filter(|_| true);
+        assert!(!channel.is_tapped());
+
+        // Tapped while the derived channels have subscribers, and only then
+        for i in 0..2 {
+            let mut subscription = chained.subscribe();
+            assert!(channel.is_tapped() && derived.is_tapped());
+            channel.push(TestPayload::new(i));
+            assert_eq!(subscription.try_pull()[0].get_payload().value(), i);
+
+            drop(subscription);
+            assert!(!channel.is_tapped() && !derived.is_tapped());
+        }
+    }
+
+    #[test]
+    fn test_derived_reentrant() {
+        let channel = Channel::<TestPayload>::new();
+        let channel_for_map = channel.clone();
+
+        // Pushes back to its source, which must not deadlock on its taps
+        let derived = channel.filter_map(move |p| {
+            if p.value() == 0 {
+                channel_for_map.push(TestPayload::new(1));
+            }
+            Some(TestPayload::new(p.value()))
+        });
+        let mut subscription = derived.subscribe();
+
+        channel.push(TestPayload::new(0));
+        let values = subscription
+            .try_pull()
+            .iter()
+            .map(|m| m.get_payload().value())
+            .collect::<Vec<_>>();
+        assert_eq!(values, [1, 0]);
+    }
+
     #[test]
     fn test_channel_eq() {
         let a = Channel::<EmptyPayload>::new();
@@ -122,6 +714,31 @@ mod tests {
         assert!(a != b);
     }
 
+    #[test]
+    fn test_latched_channel() {
+        let channel = Channel::<TestPayload>::new_latched(Latch::Count(2));
+        (0..5).for_each(|x| channel.push(TestPayload::new(x)));
+
+        let late_consumer = channel.new_consumer();
+        channel.push(TestPayload::new(5));
+
+        let values: Vec<usize> = late_consumer
+            .pull()
+            .iter()
+            .map(|m| m.get_payload().value())
+            .collect();
+        assert_eq!(values, [3, 4, 5]);
+    }
+
+    #[test]
+    fn test_not_latched_channel() {
+        let channel = Channel::<TestPayload>::new();
+        channel.push(TestPayload::new(0));
+
+        let late_consumer = channel.new_consumer();
+        assert!(late_consumer.pull().is_empty());
+    }
+
     #[test]
     fn test_channel_global() {
         let channel: Channel<TestPayload> = Channel::<TestPayload>::new();
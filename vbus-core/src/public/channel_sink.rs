@@ -0,0 +1,45 @@
+use crate::{Channel, Payload};
+use futures::Sink;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// [`Sink`] pushing every item to a [`Channel`], created by
+/// [`Channel::sink`](crate::Channel::sink).
+///
+/// Sending is always ready. Note that a subscriber with an [`OverflowPolicy::Block`] queue
+/// blocks the sending thread, like [`Channel::push`] does.
+///
+/// [`OverflowPolicy::Block`]: crate::OverflowPolicy::Block
+pub struct ChannelSink<T: Payload> {
+    channel: Channel<T>,
+}
+
+impl<T: Payload> ChannelSink<T> {
+    pub(crate) fn new(channel: &Channel<T>) -> Self {
+        Self {
+            channel: channel.clone(),
+        }
+    }
+}
+
+impl<T: Payload> Sink<T> for ChannelSink<T> {
+    type Error = Infallible;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        self.channel.push(item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
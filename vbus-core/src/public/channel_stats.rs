@@ -0,0 +1,13 @@
+use crate::ConsumerStats;
+
+/// Snapshot returned by [`Channel::stats`](crate::Channel::stats).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelStats {
+    /// Messages pushed since the channel was created.
+    pub pushed: u64,
+    /// Messages per second over the last second.
+    pub rate: f64,
+    pub subscribers: usize,
+    /// One entry per subscriber, in subscription order.
+    pub consumers: Vec<ConsumerStats>,
+}
@@ -0,0 +1,55 @@
+use crate::private::supervisor::PanicHandler;
+use crate::{OverflowPolicy, ShutdownMode, Supervision, ThreadPool};
+use std::sync::Arc;
+
+/// Options for [`Channel::new_threaded_consumer_with`](crate::Channel::new_threaded_consumer_with)
+/// and [`Channel::new_recorder_with`](crate::Channel::new_recorder_with).
+///
+/// The default is an unbounded queue processed by a dedicated thread, skipping batches that panic.
+/// Dropping a threaded consumer stops it immediately, while dropping a recorder drains it.
+#[derive(Clone, Default)]
+pub struct ConsumerConfig {
+    pub(crate) bounds: Option<(usize, OverflowPolicy)>,
+    pub(crate) pool: Option<ThreadPool>,
+    pub(crate) supervision: Supervision,
+    pub(crate) on_panic: Option<PanicHandler>,
+    pub(crate) on_drop: Option<ShutdownMode>,
+}
+
+impl ConsumerConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Holds at most `capacity` messages and applies `policy` when full. Panics if `capacity` is
+    /// 0 when the consumer is created.
+    pub fn bounded(mut self, capacity: usize, policy: OverflowPolicy) -> Self {
+        self.bounds = Some((capacity, policy));
+        self
+    }
+
+    /// Processes messages on `pool` rather than on a dedicated thread.
+    pub fn pool(mut self, pool: &ThreadPool) -> Self {
+        self.pool = Some(pool.clone());
+        self
+    }
+
+    /// What to do when the `process` closure panics.
+    pub fn supervision(mut self, supervision: Supervision) -> Self {
+        self.supervision = supervision;
+        self
+    }
+
+    /// Called with the panic message when the `process` closure panics and supervision is
+    /// [`Supervision::LogAndSkip`] or [`Supervision::Restart`].
+    pub fn on_panic(mut self, handler: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.on_panic = Some(Arc::new(handler));
+        self
+    }
+
+    /// How to stop when dropped.
+    pub fn on_drop(mut self, mode: ShutdownMode) -> Self {
+        self.on_drop = Some(mode);
+        self
+    }
+}
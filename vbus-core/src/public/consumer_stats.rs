@@ -0,0 +1,14 @@
+use std::time::Duration;
+
+/// Snapshot of one subscriber of a channel, see [`Channel::stats`](crate::Channel::stats).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsumerStats {
+    /// Messages waiting in the queue.
+    pub depth: usize,
+    /// Highest depth reached so far.
+    pub high_water_mark: usize,
+    /// Messages discarded by the overflow policy.
+    pub dropped: usize,
+    /// `None` if the queue was never pulled.
+    pub since_last_pull: Option<Duration>,
+}
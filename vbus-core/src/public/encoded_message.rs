@@ -0,0 +1,26 @@
+use std::time::Instant;
+
+/// A message whose payload is bincode-encoded, as handled by [`AnyChannel`](crate::AnyChannel).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodedMessage {
+    time_stamp: Instant,
+    bytes: Vec<u8>,
+}
+
+impl EncodedMessage {
+    pub fn new(time_stamp: Instant, bytes: Vec<u8>) -> Self {
+        Self { time_stamp, bytes }
+    }
+
+    pub fn get_type_stamp(&self) -> Instant {
+        self.time_stamp
+    }
+
+    pub fn get_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
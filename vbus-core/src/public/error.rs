@@ -8,6 +8,26 @@ pub enum Error {
     BincodeDecode(bincode::error::DecodeError),
     BincodeEncode(bincode::error::EncodeError),
     NotImplemented,
+    TypeMismatch {
+        channel: String,
+        registered: String,
+        requested: String,
+    },
+    NoServer,
+    HandlerPanicked,
+    Timeout,
+    Corrupted {
+        position: u64, // byte offset of the damaged chunk
+    },
+    ChunkTooLarge {
+        size: u64,
+        limit: u64,
+    },
+    FormatNameTooLong {
+        size: u64,
+        limit: u64,
+    },
+    NotSeekable,
 }
 
 impl From<std::io::Error> for Error {
@@ -0,0 +1,174 @@
+use crate::private::Consumer;
+use crate::private::queue::Hook;
+use crate::{Error, Message, Payload};
+use os_pipe::{PipeReader, PipeWriter};
+use std::io::{Read, Write};
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::{Arc, Mutex};
+
+/// Reader of a [`Channel`](crate::Channel) that can be waited on with `select`/`epoll` along with
+/// other file descriptors, created by [`Channel::subscribe_fd`](crate::Channel::subscribe_fd).
+///
+/// The descriptor is readable whenever messages are pending, and possibly shortly after they were
+/// pulled. It must not be read directly: call [`FdSubscription::try_pull`] when it's readable.
+pub struct FdSubscription<T: Payload> {
+    consumer: Consumer<T>,
+    reader: PipeReader,
+    armed: Arc<Mutex<Armed>>,
+    hook: Hook,
+}
+
+// Whether the pipe holds its single byte
+struct Armed {
+    armed: bool,
+    writer: PipeWriter,
+}
+
+impl<T: Payload> FdSubscription<T> {
+    pub(crate) fn new(consumer: Consumer<T>) -> Result<Self, Error> {
+        let (reader, writer) = os_pipe::pipe()?;
+        let armed = Arc::new(Mutex::new(Armed {
+            armed: false,
+            writer,
+        }));
+
+        let armed_for_hook = armed.clone();
+        let hook: Hook = Arc::new(move || {
+            let mut lock = armed_for_hook.lock().unwrap();
+            // Runs in the pushing thread, which must not fail: if the byte can't be written, the
+            // descriptor just stays unreadable until the next push
+            if !lock.armed && lock.writer.write_all(&[0]).is_ok() {
+                lock.armed = true; // can't have blocked: the pipe was empty
+            }
+        });
+
+        let subscription = Self {
+            consumer,
+            reader,
+            armed,
+            hook,
+        };
+
+        subscription.consumer.queue().add_hook(&subscription.hook);
+        subscription.arm_if_pending(); // latched channels may have prefilled the queue
+        Ok(subscription)
+    }
+
+    /// Returns all queued messages without blocking, possibly none.
+    pub fn try_pull(&mut self) -> Vec<Message<T>> {
+        let mut lock = self.armed.lock().unwrap();
+        let messages = self.consumer.pull();
+
+        // Under the lock: a push after this check arms the pipe again
+        if lock.armed && self.consumer.queue().len() == 0 {
+            let mut byte = [0u8];
+            self.reader.read_exact(&mut byte).unwrap(); // can't block: the pipe is armed
+            lock.armed = false;
+        }
+
+        messages
+    }
+
+    /// Number of messages discarded by the queue overflow policy.
+    pub fn dropped(&self) -> usize {
+        self.consumer.queue().dropped()
+    }
+
+    fn arm_if_pending(&self) {
+        if self.consumer.queue().len() > 0 {
+            (self.hook)();
+        }
+    }
+}
+
+impl<T: Payload> AsRawFd for FdSubscription<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.reader.as_raw_fd()
+    }
+}
+
+impl<T: Payload> Drop for FdSubscription<T> {
+    fn drop(&mut self) {
+        self.consumer.queue().remove_hook(&self.hook);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::private::test_tools::TestPayload;
+    use crate::tools::pipe_flag::pipe_flag;
+    use crate::{Channel, Latch};
+    use std::os::fd::AsRawFd;
+    use std::time::Duration;
+
+    #[test]
+    fn test_fd_subscription() {
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe_fd().unwrap();
+        let (stop_reader, mut stop_writer) = pipe_flag();
+
+        let mut selector = selecting::Selector::new();
+        selector.add_read(&subscription.as_raw_fd());
+        selector.add_read(&stop_reader.as_raw_fd());
+
+        let result = selector.select_timeout(Duration::ZERO).unwrap();
+        assert!(!result.is_read(&subscription.as_raw_fd()));
+
+        (0..3).for_each(|x| channel.push(TestPayload::new(x)));
+        let result = selector.select_timeout(Duration::ZERO).unwrap();
+        assert!(result.is_read(&subscription.as_raw_fd()));
+        assert!(!result.is_read(&stop_reader.as_raw_fd()));
+
+        assert_eq!(subscription.try_pull().len(), 3);
+        let result = selector.select_timeout(Duration::ZERO).unwrap();
+        assert!(!result.is_read(&subscription.as_raw_fd()));
+
+        // Spurious readiness is harmless
+        assert!(subscription.try_pull().is_empty());
+
+        stop_writer.raise();
+        channel.push(TestPayload::new(3));
+        let result = selector.select().unwrap();
+        assert!(result.is_read(&subscription.as_raw_fd()));
+        assert!(result.is_read(&stop_reader.as_raw_fd()));
+        assert_eq!(subscription.try_pull().len(), 1);
+    }
+
+    #[test]
+    fn test_latched_fd_subscription() {
+        let channel = Channel::<TestPayload>::new_latched(Latch::Count(1));
+        channel.push(TestPayload::new(0));
+
+        let mut subscription = channel.subscribe_fd().unwrap();
+        let mut selector = selecting::Selector::new();
+        selector.add_read(&subscription.as_raw_fd());
+
+        let result = selector.select_timeout(Duration::ZERO).unwrap();
+        assert!(result.is_read(&subscription.as_raw_fd()));
+        assert_eq!(subscription.try_pull().len(), 1);
+    }
+
+    #[test]
+    fn test_many_pushes() {
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe_fd().unwrap();
+
+        let channel_for_thread = channel.clone();
+        let join_handle = std::thread::spawn(move || {
+            (0..10000).for_each(|x| channel_for_thread.push(TestPayload::new(x)));
+        });
+
+        let mut selector = selecting::Selector::new();
+        selector.add_read(&subscription.as_raw_fd());
+
+        let mut received = 0;
+        while received < 10000 {
+            let result = selector.select_timeout(Duration::from_secs(5)).unwrap();
+            assert!(result.is_read(&subscription.as_raw_fd()));
+            received += subscription.try_pull().len();
+        }
+
+        join_handle.join().unwrap();
+        assert_eq!(received, 10000);
+    }
+}
@@ -0,0 +1,23 @@
+//! Entry points for the fuzz targets, into what the public API can't reach. Not part of the API.
+
+use crate::private::io::InputStream;
+use crate::{Payload, ReadLimits};
+use std::io::Cursor;
+
+/// Reads `bytes` as a recording the way [`Player`](crate::Player) does, with the default limits,
+/// then as a seekable recording, seeking to message `seek` first if given.
+pub fn read_stream<T: Payload>(bytes: &[u8], recovery: bool, seek: Option<u64>) {
+    if let Ok(mut stream) = InputStream::<T>::new(Box::new(Cursor::new(bytes.to_vec()))) {
+        stream.set_recovery(recovery);
+        while stream.get().is_ok() {}
+    }
+
+    let read = Box::new(Cursor::new(bytes.to_vec()));
+    if let Ok(mut stream) = InputStream::<T>::new_seekable(read, &ReadLimits::default()) {
+        stream.set_recovery(recovery);
+        if let Some(n) = seek {
+            let _ = stream.seek_to_message(n);
+        }
+        while stream.get().is_ok() {}
+    }
+}
@@ -0,0 +1,10 @@
+use std::time::Duration;
+
+/// How much history a latched [`Channel`](crate::Channel) replays to new subscribers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Latch {
+    /// Keep the last N messages.
+    Count(usize),
+    /// Keep the messages pushed within the given duration.
+    Duration(Duration),
+}
@@ -0,0 +1,143 @@
+use crate::private::Consumer;
+use crate::private::queue::{Queue, Waker};
+use crate::{Message, Payload};
+use std::time::{Duration, Instant};
+
+/// Conflating reader of a [`Channel`](crate::Channel), created by
+/// [`Channel::subscribe_latest`](crate::Channel::subscribe_latest).
+///
+/// Only the most recent message is kept, so a slow reader never builds a backlog.
+pub struct LatestSubscription<T: Payload> {
+    consumer: Consumer<T>, // capacity 1, drop oldest
+    last: Option<Message<T>>,
+}
+
+impl<T: Payload> LatestSubscription<T> {
+    pub(crate) fn new(consumer: Consumer<T>) -> Self {
+        Self {
+            consumer,
+            last: None,
+        }
+    }
+
+    pub(crate) fn queue(&self) -> &Queue<T> {
+        self.consumer.queue()
+    }
+
+    /// Returns the most recent message without blocking, or `None` if nothing was pushed since
+    /// the subscription was created. The same message is returned until a newer one arrives.
+    pub fn latest(&mut self) -> Option<Message<T>> {
+        self.update(self.consumer.pull());
+        self.last.clone()
+    }
+
+    /// Blocks until a message newer than the last one returned is available.
+    pub fn wait_newer(&mut self) -> Message<T> {
+        loop {
+            if let Some(message) = self.update(self.consumer.wait_pull()) {
+                return message;
+            }
+        }
+    }
+
+    /// Like [`LatestSubscription::wait_newer`], but returns `None` after `timeout`.
+    pub fn wait_newer_timeout(&mut self, timeout: Duration) -> Option<Message<T>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let messages = self
+                .consumer
+                .wait_pull_timeout(deadline.saturating_duration_since(Instant::now()));
+            if let Some(message) = self.update(messages) {
+                return Some(message);
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+        }
+    }
+
+    /// Returns a handle that cancels a [`Select`](crate::Select) waiting on this subscription.
+    pub fn waker(&self) -> Waker<T> {
+        self.queue().waker()
+    }
+
+    /// Number of messages that were superseded before being read.
+    pub fn skipped(&self) -> usize {
+        self.consumer.queue().dropped()
+    }
+
+    fn update(&mut self, messages: Vec<Message<T>>) -> Option<Message<T>> {
+        let newest = messages.into_iter().last()?;
+        self.last = Some(newest.clone());
+        Some(newest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Channel;
+    use crate::private::test_tools::TestPayload;
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_latest() {
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe_latest();
+
+        assert!(subscription.latest().is_none());
+
+        (0..10).for_each(|x| channel.push(TestPayload::new(x)));
+        subscription.latest().unwrap().get_payload().check(9);
+        subscription.latest().unwrap().get_payload().check(9);
+        assert_eq!(subscription.skipped(), 9);
+
+        channel.push(TestPayload::new(10));
+        subscription.latest().unwrap().get_payload().check(10);
+    }
+
+    #[test]
+    fn test_wait_newer() {
+        const WAIT_TIME: Duration = Duration::from_millis(200);
+
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe_latest();
+
+        channel.push(TestPayload::new(0));
+        subscription.wait_newer().get_payload().check(0);
+
+        let channel_for_thread = channel.clone();
+        let start_time = Instant::now();
+        let join_handle = std::thread::spawn(move || {
+            sleep(WAIT_TIME);
+            channel_for_thread.push(TestPayload::new(1));
+        });
+
+        subscription.wait_newer().get_payload().check(1);
+        assert!(start_time.elapsed() >= WAIT_TIME);
+
+        join_handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_wait_newer_timeout() {
+        const WAIT_TIME: Duration = Duration::from_millis(200);
+
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe_latest();
+
+        channel.push(TestPayload::new(0));
+        subscription.latest().unwrap();
+
+        let start_time = Instant::now();
+        assert!(subscription.wait_newer_timeout(WAIT_TIME).is_none());
+        assert!(start_time.elapsed() >= WAIT_TIME);
+
+        channel.push(TestPayload::new(1));
+        subscription
+            .wait_newer_timeout(WAIT_TIME)
+            .unwrap()
+            .get_payload()
+            .check(1);
+    }
+}
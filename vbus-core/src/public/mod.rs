@@ -1,16 +1,76 @@
+mod any_channel;
+mod any_recorder;
+mod any_subscription;
+#[cfg(feature = "async")]
+mod async_subscription;
+mod bus;
 mod channel;
+#[cfg(feature = "async")]
+mod channel_sink;
+mod channel_stats;
+mod consumer_config;
+mod consumer_stats;
+mod encoded_message;
 mod error;
+mod fd_subscription;
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+mod latch;
+mod latest_subscription;
 mod message;
+mod overflow_policy;
 mod payload;
 mod player;
+mod reactor;
+mod read_limits;
 mod recorder;
+mod recording_loss;
+mod recording_reader;
+mod select;
+mod service;
+mod shutdown_mode;
+mod subscription;
+mod supervision;
+mod sync_policy;
+mod synchronizer;
+mod thread_pool;
 mod threaded_consumer;
 pub mod tools;
 
+pub use crate::private::queue::Waker;
+pub use any_channel::AnyChannel;
+pub use any_recorder::AnyRecorder;
+pub use any_subscription::AnySubscription;
+#[cfg(feature = "async")]
+pub use async_subscription::AsyncSubscription;
+pub use bus::Bus;
 pub use channel::Channel;
+#[cfg(feature = "async")]
+pub use channel_sink::ChannelSink;
+pub use channel_stats::ChannelStats;
+pub use consumer_config::ConsumerConfig;
+pub use consumer_stats::ConsumerStats;
+pub use encoded_message::EncodedMessage;
 pub use error::Error;
+pub use fd_subscription::FdSubscription;
+pub use latch::Latch;
+pub use latest_subscription::LatestSubscription;
 pub use message::Message;
+pub use overflow_policy::OverflowPolicy;
 pub use payload::Payload;
 pub use player::Player;
+pub use reactor::{Reactor, ReactorStopper, SourceId, SourceRemover};
+pub use read_limits::ReadLimits;
 pub use recorder::Recorder;
+pub use recording_loss::RecordingLoss;
+pub use recording_reader::{RecordedMessage, RecordingReader};
+pub use select::{Select, SelectHandle, Selectable};
+pub use service::{Reply, Service, ServiceServer};
+pub use shutdown_mode::ShutdownMode;
+pub use subscription::Subscription;
+pub use supervision::Supervision;
+pub use sync_policy::SyncPolicy;
+pub use synchronizer::{Synchronize, Synchronizer};
+pub use thread_pool::ThreadPool;
 pub use threaded_consumer::ThreadedConsumer;
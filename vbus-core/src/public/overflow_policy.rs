@@ -0,0 +1,10 @@
+/// What a bounded queue does when a message arrives while it is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued message to make room for the new one.
+    DropOldest,
+    /// Discard the incoming message, keeping the queue as is.
+    DropNewest,
+    /// Block the pusher until the consumer makes room.
+    Block,
+}
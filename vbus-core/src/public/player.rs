@@ -41,8 +41,6 @@ impl<T: Payload> Drop for Player<T> {
 mod tests {
     use crate::Channel;
     use crate::private::test_tools::{TestDataSet, TestPayload, random_message_sequence};
-    use std::thread::sleep;
-    use std::time::Duration;
 
     #[test]
     fn test_player() {
@@ -51,9 +49,9 @@ mod tests {
 
         let channel = Channel::<TestPayload>::new();
         let consumer = channel.new_consumer();
-        let _player = channel.new_player(&dataset).unwrap();
+        let player = channel.new_player(&dataset).unwrap();
 
-        sleep(Duration::from_secs(1)); // should be more than enough
+        drop(player); // waits for the end of the playback
         let buffer = consumer.pull();
 
         assert_eq!(buffer.len(), data.len());
@@ -0,0 +1,441 @@
+use crate::tools::pipe_flag::*;
+use crate::{Error, FdSubscription, Message, Payload};
+use os_pipe::{PipeReader, PipeWriter};
+use selecting::Selector;
+use std::io::{Read, Write};
+use std::ops::ControlFlow;
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+/// Single-threaded event loop dispatching callbacks when file descriptors become readable, when
+/// timers expire and when subscriptions receive messages. Sources are removed when their callback
+/// returns [`ControlFlow::Break`].
+///
+/// Meant to replace one thread per sensor or consumer on constrained targets: callbacks must not
+/// block.
+pub struct Reactor {
+    sources: Vec<Source>,
+    next_id: usize,
+    stop_reader: PipeFlagReader,
+    stop_writer: Arc<Mutex<PipeFlagWriter>>,
+    removals: Arc<Removals>,
+    removals_reader: PipeReader, // readable once a removal is requested
+}
+
+/// Identifies a source in a [`Reactor`], for [`Reactor::remove`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SourceId(usize);
+
+/// Stops a [`Reactor`] from any thread, or from one of its callbacks.
+#[derive(Clone)]
+pub struct ReactorStopper {
+    writer: Arc<Mutex<PipeFlagWriter>>,
+}
+
+/// Removes sources from a [`Reactor`] from any thread, or from one of its callbacks, e.g. when
+/// the object that added them is dropped. They are not called anymore once this returns: from
+/// another thread, it waits for the callback being called, if any, to return.
+#[derive(Clone)]
+pub struct SourceRemover {
+    removals: Arc<Removals>,
+}
+
+type Callback = Box<dyn FnMut() -> ControlFlow<()>>;
+
+struct Removals {
+    requested: Mutex<Requested>,
+    writer: PipeWriter,
+    dispatch: Mutex<()>, // held by the reactor while it calls a callback
+    thread: ThreadId,    // of the reactor, which can't be sent to another one
+}
+
+struct Requested {
+    ids: Vec<SourceId>,
+    signalled: bool, // whether the pipe holds its single byte, so that writing never blocks
+}
+
+struct Source {
+    id: SourceId,
+    kind: SourceKind,
+    callback: Callback,
+}
+
+enum SourceKind {
+    Fd(RawFd),
+    Timer { next: Instant, period: Duration },
+}
+
+impl Reactor {
+    /// Fails if the pipes it waits on can't be created, e.g. when the process is out of file
+    /// descriptors.
+    pub fn new() -> Result<Self, Error> {
+        let (stop_reader, stop_writer) = try_pipe_flag()?;
+        let (removals_reader, removals_writer) = os_pipe::pipe()?;
+
+        Ok(Self {
+            sources: Vec::new(),
+            next_id: 0,
+            stop_reader,
+            stop_writer: Arc::new(Mutex::new(stop_writer)),
+            removals: Arc::new(Removals {
+                requested: Mutex::new(Requested {
+                    ids: Vec::new(),
+                    signalled: false,
+                }),
+                writer: removals_writer,
+                dispatch: Mutex::new(()),
+                thread: std::thread::current().id(),
+            }),
+            removals_reader,
+        })
+    }
+
+    /// Calls `callback` whenever `fd` is readable. The callback must consume what made it
+    /// readable, or it will be called again right away.
+    pub fn add_fd(
+        &mut self,
+        fd: RawFd,
+        callback: impl FnMut() -> ControlFlow<()> + 'static,
+    ) -> SourceId {
+        self.add(SourceKind::Fd(fd), Box::new(callback))
+    }
+
+    /// Calls `callback` every `period`, starting one period from now. Late calls don't shift the
+    /// following ones, and missed periods are skipped.
+    pub fn add_timer(
+        &mut self,
+        period: Duration,
+        callback: impl FnMut() -> ControlFlow<()> + 'static,
+    ) -> SourceId {
+        let next = Instant::now() + period;
+        self.add(SourceKind::Timer { next, period }, Box::new(callback))
+    }
+
+    /// Calls `callback` with the messages of `subscription` whenever there are some.
+    pub fn add_subscription<T: Payload>(
+        &mut self,
+        mut subscription: FdSubscription<T>,
+        mut callback: impl FnMut(Vec<Message<T>>) -> ControlFlow<()> + 'static,
+    ) -> SourceId {
+        let fd = subscription.as_raw_fd();
+
+        self.add_fd(fd, move || {
+            let messages = subscription.try_pull();
+            match messages.is_empty() {
+                true => ControlFlow::Continue(()), // spurious readiness
+                false => callback(messages),
+            }
+        })
+    }
+
+    /// Returns false if there was no such source.
+    pub fn remove(&mut self, id: SourceId) -> bool {
+        let len = self.sources.len();
+        self.sources.retain(|source| source.id != id);
+        self.sources.len() != len
+    }
+
+    pub fn stopper(&self) -> ReactorStopper {
+        ReactorStopper {
+            writer: self.stop_writer.clone(),
+        }
+    }
+
+    pub fn remover(&self) -> SourceRemover {
+        SourceRemover {
+            removals: self.removals.clone(),
+        }
+    }
+
+    /// Dispatches events until stopped, or until there are no sources left.
+    pub fn run(&mut self) -> Result<(), Error> {
+        loop {
+            self.apply_removals();
+            if self.sources.is_empty() || !self.run_once(None)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Waits for events for at most `timeout`, or until the next timer if `None`, and dispatches
+    /// them. Returns false if the reactor was stopped, which is final.
+    pub fn run_once(&mut self, timeout: Option<Duration>) -> Result<bool, Error> {
+        self.apply_removals();
+
+        let mut selector = Selector::new();
+        selector.add_read(&self.stop_reader);
+        selector.add_read(&self.removals_reader);
+
+        let mut deadline = timeout.map(|timeout| Instant::now() + timeout);
+        for source in &self.sources {
+            match source.kind {
+                SourceKind::Fd(fd) => selector.add_read(&Fd(fd)),
+                SourceKind::Timer { next, .. } => {
+                    deadline = Some(deadline.map_or(next, |d| d.min(next)))
+                }
+            }
+        }
+
+        let result = match deadline {
+            None => selector.select()?,
+            Some(deadline) => {
+                selector.select_timeout(deadline.saturating_duration_since(Instant::now()))?
+            }
+        };
+
+        if result.is_read(&self.stop_reader) {
+            return Ok(false);
+        }
+
+        if result.is_read(&self.removals_reader) {
+            let mut requested = self.removals.requested.lock().unwrap();
+            let mut byte = [0u8; 1];
+            self.removals_reader.read_exact(&mut byte)?;
+            requested.signalled = false;
+        }
+
+        let now = Instant::now();
+        let removals = &self.removals;
+        self.sources.retain_mut(|source| {
+            // Checked under the dispatch lock: a removal from another thread either comes first,
+            // or waits for the callback to return
+            let _dispatch = removals.dispatch.lock().unwrap();
+            if removals.requested.lock().unwrap().ids.contains(&source.id) {
+                return false; // removed by another thread or a previous callback
+            }
+
+            let ready = match &mut source.kind {
+                SourceKind::Fd(fd) => result.is_read(&Fd(*fd)),
+                SourceKind::Timer { next, period } => {
+                    let ready = now >= *next;
+                    if ready {
+                        *next += *period;
+                        if *next <= now {
+                            *next = now + *period; // skip missed periods
+                        }
+                    }
+                    ready
+                }
+            };
+
+            !ready || (source.callback)().is_continue()
+        });
+
+        self.apply_removals();
+        Ok(true)
+    }
+
+    fn apply_removals(&mut self) {
+        let ids = std::mem::take(&mut self.removals.requested.lock().unwrap().ids);
+        if !ids.is_empty() {
+            self.sources.retain(|source| !ids.contains(&source.id));
+        }
+    }
+
+    fn add(&mut self, kind: SourceKind, callback: Callback) -> SourceId {
+        let id = SourceId(self.next_id);
+        self.next_id += 1;
+        self.sources.push(Source { id, kind, callback });
+        id
+    }
+}
+
+impl ReactorStopper {
+    pub fn stop(&self) {
+        self.writer.lock().unwrap().raise();
+    }
+}
+
+impl SourceRemover {
+    pub fn remove(&self, id: SourceId) {
+        {
+            let mut requested = self.removals.requested.lock().unwrap();
+            requested.ids.push(id);
+
+            // Wakes the reactor up. If that fails, the removal is applied on its next wake-up.
+            if !requested.signalled && (&self.removals.writer).write(&[0]).is_ok_and(|n| n == 1) {
+                requested.signalled = true;
+            }
+        }
+
+        // The reactor's own thread can't be calling another callback
+        if std::thread::current().id() != self.removals.thread {
+            drop(self.removals.dispatch.lock().unwrap());
+        }
+    }
+}
+
+// selecting wants AsRawFd
+struct Fd(RawFd);
+
+impl AsRawFd for Fd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Channel;
+    use crate::private::test_tools::TestPayload;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_reactor_subscriptions() {
+        let a = Channel::<TestPayload>::new();
+        let b = Channel::<TestPayload>::new();
+        let received = Rc::new(RefCell::new(Vec::<(char, usize)>::new()));
+
+        let mut reactor = Reactor::new().unwrap();
+        for (name, channel) in [('a', &a), ('b', &b)] {
+            let received = received.clone();
+            reactor.add_subscription(channel.subscribe_fd().unwrap(), move |messages| {
+                let mut received = received.borrow_mut();
+                messages
+                    .iter()
+                    .for_each(|m| received.push((name, m.get_payload().value())));
+                match received.len() {
+                    3 => ControlFlow::Break(()),
+                    _ => ControlFlow::Continue(()),
+                }
+            });
+        }
+
+        let join_handle = std::thread::spawn(move || {
+            a.push(TestPayload::new(0));
+            std::thread::sleep(Duration::from_millis(50));
+            b.push(TestPayload::new(1));
+            std::thread::sleep(Duration::from_millis(50));
+            a.push(TestPayload::new(2));
+        });
+
+        // Until all the messages are received
+        while received.borrow().len() < 3 {
+            reactor.run_once(Some(Duration::from_secs(5))).unwrap();
+        }
+        join_handle.join().unwrap();
+
+        assert_eq!(*received.borrow(), [('a', 0), ('b', 1), ('a', 2)]);
+        assert_eq!(reactor.sources.len(), 1); // the first one to reach 3 is gone
+    }
+
+    #[test]
+    fn test_reactor_timers() {
+        const PERIOD: Duration = Duration::from_millis(20);
+
+        let ticks = Rc::new(RefCell::new(0));
+        let ticks_for_timer = ticks.clone();
+
+        let mut reactor = Reactor::new().unwrap();
+        reactor.add_timer(PERIOD, move || {
+            *ticks_for_timer.borrow_mut() += 1;
+            match *ticks_for_timer.borrow() {
+                5 => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            }
+        });
+
+        let start_time = Instant::now();
+        reactor.run().unwrap(); // returns once the timer is removed
+        assert_eq!(*ticks.borrow(), 5);
+        assert!(start_time.elapsed() >= PERIOD * 5);
+    }
+
+    #[test]
+    fn test_reactor_stop_and_remove() {
+        let mut reactor = Reactor::new().unwrap();
+        let stopper = reactor.stopper();
+
+        let id = reactor.add_timer(Duration::from_secs(3600), || ControlFlow::Continue(()));
+        reactor.add_timer(Duration::from_millis(10), move || {
+            stopper.stop();
+            ControlFlow::Continue(())
+        });
+
+        reactor.run().unwrap();
+        assert!(!reactor.run_once(None).unwrap()); // stopping is final
+
+        assert!(reactor.remove(id));
+        assert!(!reactor.remove(id));
+    }
+
+    #[test]
+    fn test_reactor_remover() {
+        let mut reactor = Reactor::new().unwrap();
+        let remover = reactor.remover();
+        let (reader, _writer) = os_pipe::pipe().unwrap();
+
+        // Never readable: run() only returns once it is removed
+        let id = reactor.add_fd(reader.as_raw_fd(), || ControlFlow::Continue(()));
+        let join_handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            remover.remove(id);
+        });
+
+        reactor.run().unwrap();
+        join_handle.join().unwrap();
+        assert!(reactor.sources.is_empty());
+
+        // From a callback, before the other source is called
+        let called = Rc::new(RefCell::new(false));
+        let called_for_callback = called.clone();
+        let second = Rc::new(RefCell::new(None));
+        let second_for_callback = second.clone();
+        let remover = reactor.remover();
+
+        reactor.add_timer(Duration::ZERO, move || {
+            remover.remove(second_for_callback.borrow().unwrap());
+            ControlFlow::Break(())
+        });
+        *second.borrow_mut() = Some(reactor.add_timer(Duration::ZERO, move || {
+            *called_for_callback.borrow_mut() = true;
+            ControlFlow::Continue(())
+        }));
+
+        reactor.run().unwrap();
+        assert!(!*called.borrow());
+    }
+
+    #[test]
+    fn test_reactor_remover_waits_for_callback() {
+        const WAIT_TIME: Duration = Duration::from_millis(100);
+
+        let mut reactor = Reactor::new().unwrap();
+        let remover = reactor.remover();
+        let running = Arc::new(Mutex::new(false));
+        let running_for_callback = running.clone();
+        let (started_sender, started) = std::sync::mpsc::channel();
+
+        let id = reactor.add_timer(Duration::ZERO, move || {
+            *running_for_callback.lock().unwrap() = true;
+            let _ = started_sender.send(());
+            std::thread::sleep(WAIT_TIME);
+            *running_for_callback.lock().unwrap() = false;
+            ControlFlow::Continue(())
+        });
+
+        let join_handle = std::thread::spawn(move || {
+            started.recv().unwrap();
+            remover.remove(id);
+            assert!(!*running.lock().unwrap()); // returned once the callback was done
+        });
+
+        reactor.run().unwrap();
+        join_handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_reactor_many_removals() {
+        let reactor = Reactor::new().unwrap();
+        let remover = reactor.remover();
+
+        // Not running: more removals than the pipe can hold must not block
+        for i in 0..100_000 {
+            remover.remove(SourceId(i));
+        }
+    }
+}
@@ -0,0 +1,51 @@
+/// Limits on what recording readers accept, so that a damaged or crafted file fails with an
+/// error rather than exhausting memory.
+///
+/// The default accepts chunks up to 256 MiB, format names up to 1 KiB and version 1 recordings.
+#[derive(Clone, Debug)]
+pub struct ReadLimits {
+    pub(crate) max_chunk_size: u64,
+    pub(crate) max_format_name: u64,
+    pub(crate) version_1: bool,
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self {
+        Self {
+            max_chunk_size: 256 << 20,
+            max_format_name: 1 << 10,
+            version_1: true,
+        }
+    }
+}
+
+impl ReadLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Larger encoded payloads fail with [`Error::ChunkTooLarge`](crate::Error::ChunkTooLarge).
+    ///
+    /// Also bounds the memory a payload can allocate while being decoded, rounded up to 1, 16 or
+    /// 256 MiB: payloads that announce more fail with
+    /// [`Error::BincodeDecode`](crate::Error::BincodeDecode). Above 256 MiB, it is not bounded.
+    pub fn max_chunk_size(mut self, bytes: u64) -> Self {
+        self.max_chunk_size = bytes;
+        self
+    }
+
+    /// Longer format names fail with
+    /// [`Error::FormatNameTooLong`](crate::Error::FormatNameTooLong).
+    pub fn max_format_name(mut self, bytes: u64) -> Self {
+        self.max_format_name = bytes;
+        self
+    }
+
+    /// Whether version 1 recordings are read. They have no checksums, so damage goes unnoticed,
+    /// and their time stamps only make sense on the machine and boot that wrote them: refuse them
+    /// when reading untrusted files.
+    pub fn version_1(mut self, allowed: bool) -> Self {
+        self.version_1 = allowed;
+        self
+    }
+}
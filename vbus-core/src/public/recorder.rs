@@ -1,36 +1,56 @@
-use crate::ThreadedConsumer;
+use crate::private::Consumer;
 use crate::private::io::OutputStream;
-use crate::{Channel, Error, Payload};
+use crate::private::supervisor::{PanicHandler, Supervisor};
+use crate::{Error, Message, Payload, ShutdownMode, Supervision, ThreadPool, ThreadedConsumer};
 use std::fs::File;
 use std::path::Path;
+use std::time::Duration;
 
 pub struct Recorder<T: Payload> {
-    _tc: ThreadedConsumer<T>,
+    tc: ThreadedConsumer<T>,
 }
 
 impl<T: Payload> Recorder<T> {
-    pub(crate) fn new(channel: &Channel<T>, path: &Path) -> Result<Self, Error> {
+    pub(crate) fn new(
+        consumer: Consumer<T>,
+        pool: Option<&ThreadPool>,
+        supervision: Supervision,
+        on_panic: Option<PanicHandler>,
+        on_drop: ShutdownMode,
+        path: &Path,
+    ) -> Result<Self, Error> {
         let file = Box::new(File::create(path)?);
         let mut ostream = OutputStream::<T>::new(file)?;
 
-        let tc = ThreadedConsumer::new(channel.new_consumer(), move |messages| {
+        let supervisor = Supervisor::new(supervision, move |messages: Vec<Message<T>>| {
             for m in messages {
                 ostream.append(&m).unwrap(); // TODO handle error here
             }
-        });
+        })
+        .on_panic(on_panic);
+        let tc = ThreadedConsumer::new_on(consumer, supervisor, pool, on_drop);
 
-        Ok(Self { _tc: tc })
+        Ok(Self { tc })
+    }
+
+    /// Number of messages discarded by the queue overflow policy.
+    pub fn dropped(&self) -> usize {
+        self.tc.dropped()
+    }
+
+    /// See [`ThreadedConsumer::shutdown`]. Dropping a recorder drains it by default.
+    pub fn shutdown(self, mode: ShutdownMode, timeout: Option<Duration>) -> usize {
+        self.tc.shutdown(mode, timeout)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Message;
     use crate::private::io::InputStream;
     use crate::private::test_tools::TempFile;
     use crate::private::test_tools::TestPayload;
-    use std::thread::sleep;
+    use crate::{Channel, Message};
     use std::time::{Duration, Instant};
 
     #[test]
@@ -46,13 +66,14 @@ mod tests {
         record(&temp_file, &reference).unwrap();
         let actual = read(&temp_file).unwrap();
 
+        // Recordings keep time stamps relative to each other
         let ref_ts = reference
             .iter()
-            .map(|m| m.get_type_stamp())
+            .map(|m| m.get_type_stamp() - reference[0].get_type_stamp())
             .collect::<Vec<_>>();
         let actual_ts = actual
             .iter()
-            .map(|m| m.get_type_stamp())
+            .map(|m| m.get_type_stamp() - actual[0].get_type_stamp())
             .collect::<Vec<_>>();
         assert_eq!(actual_ts, ref_ts);
 
@@ -66,15 +87,39 @@ mod tests {
 
     fn record(path: &Path, data: &[Message<TestPayload>]) -> Result<(), Error> {
         let channel: Channel<TestPayload> = Channel::<TestPayload>::new();
-        let _recorder = Recorder::new(&channel, path)?;
+        let _recorder = channel.new_recorder(path)?;
 
         data.iter().for_each(|x| channel.push_message(x.clone()));
-
-        sleep(Duration::from_millis(500));
+        channel.flush();
 
         Ok(())
     }
 
+    #[test]
+    fn test_recorder_drain_on_drop() {
+        let temp_file = TempFile::new().unwrap();
+        let channel = Channel::<TestPayload>::new();
+        let recorder = channel.new_recorder(&temp_file).unwrap();
+
+        (0..1000).for_each(|x| channel.push(TestPayload::new(x)));
+        drop(recorder); // no sleep: the tail must still be written
+
+        assert_eq!(read(&temp_file).unwrap().len(), 1000);
+    }
+
+    #[test]
+    fn test_recorder_shutdown() {
+        let temp_file = TempFile::new().unwrap();
+        let channel = Channel::<TestPayload>::new();
+        let recorder = channel.new_recorder(&temp_file).unwrap();
+
+        (0..1000).for_each(|x| channel.push(TestPayload::new(x)));
+        assert_eq!(recorder.shutdown(ShutdownMode::Drain, None), 0);
+
+        channel.push(TestPayload::new(1000)); // not recorded anymore
+        assert_eq!(read(&temp_file).unwrap().len(), 1000);
+    }
+
     fn read(path: &Path) -> Result<Vec<Message<TestPayload>>, Error> {
         let file = File::open(path)?;
         let mut istream = InputStream::<TestPayload>::new(Box::new(file))?;
@@ -0,0 +1,12 @@
+use std::ops::Range;
+
+/// Damaged part of a recording, skipped by a recovering
+/// [`RecordingReader`](crate::RecordingReader).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordingLoss {
+    /// Byte offsets in the recording.
+    pub bytes: Range<u64>,
+    /// Chunk indices: chunk 0 holds the format name, and message `n` is in chunk `n + 1`. Empty
+    /// when bytes were damaged without losing a chunk.
+    pub chunks: Range<u64>,
+}
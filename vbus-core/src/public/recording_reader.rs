@@ -0,0 +1,312 @@
+use crate::private::io::{InputStream, offset_to_system_time, system_time_to_offset};
+use crate::{Error, Message, Payload, ReadLimits, RecordingLoss};
+use std::io::Read;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Reads a recording made by a [`Recorder`](crate::Recorder), with the time at which each message
+/// was stamped. Iterating stops at the end of the recording, or after the first error.
+///
+/// Damaged chunks fail with [`Error::Corrupted`], unless opened with
+/// [`RecordingReader::open_recovering`].
+///
+/// Recordings opened from a path can be sought in O(log n), thanks to the index written at their
+/// end. When it is missing, e.g. after a crash, the first seek rebuilds it by scanning the file.
+pub struct RecordingReader<T: Payload> {
+    stream: InputStream<T>,
+    failed: bool,
+}
+
+/// Message read by a [`RecordingReader`].
+pub struct RecordedMessage<T: Payload> {
+    message: Message<T>,
+    offset: i64,
+    wall_time: SystemTime,
+}
+
+impl<T: Payload> RecordingReader<T> {
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        Self::open_with(path, &ReadLimits::default())
+    }
+
+    pub fn open_with(path: &Path, limits: &ReadLimits) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Ok(Self {
+            stream: InputStream::new_seekable(Box::new(file), limits)?,
+            failed: false,
+        })
+    }
+
+    /// Reads a recording from any source, e.g. a socket or memory. Such readers can't seek.
+    pub fn from_read(read: impl Read + Send + 'static, limits: &ReadLimits) -> Result<Self, Error> {
+        Ok(Self {
+            stream: InputStream::new_with(Box::new(read), limits)?,
+            failed: false,
+        })
+    }
+
+    /// Like [`RecordingReader::open`], but damaged chunks and a truncated end are skipped, and
+    /// reported by [`RecordingReader::losses`].
+    pub fn open_recovering(path: &Path) -> Result<Self, Error> {
+        let mut reader = Self::open(path)?;
+        reader.set_recovery(true);
+        Ok(reader)
+    }
+
+    /// Whether damaged chunks and a truncated end are skipped from now on.
+    pub fn set_recovery(&mut self, recovery: bool) {
+        self.stream.set_recovery(recovery);
+    }
+
+    /// What was skipped so far, in recovery mode.
+    pub fn losses(&self) -> &[RecordingLoss] {
+        self.stream.losses()
+    }
+
+    /// Wall-clock time at which the recording started.
+    pub fn anchor(&self) -> SystemTime {
+        self.stream.anchor()
+    }
+
+    /// Moves to the first message whose wall time is `time` or later, or to the end, assuming
+    /// that time stamps don't go back. Fails with [`Error::NotSeekable`] for readers made with
+    /// [`RecordingReader::from_read`] and for version 1 recordings.
+    pub fn seek_to_time(&mut self, time: SystemTime) -> Result<(), Error> {
+        self.stream
+            .seek_to_offset(system_time_to_offset(self.anchor(), time))?;
+        self.failed = false;
+        Ok(())
+    }
+
+    /// Moves to message `index`, counted from 0, or to the end if there are fewer messages.
+    pub fn seek_to_index(&mut self, index: u64) -> Result<(), Error> {
+        self.stream.seek_to_message(index)?;
+        self.failed = false;
+        Ok(())
+    }
+}
+
+impl<T: Payload> Iterator for RecordingReader<T> {
+    type Item = Result<RecordedMessage<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.stream.get_with_offset() {
+            Ok((message, offset)) => Some(Ok(RecordedMessage {
+                message,
+                offset,
+                wall_time: offset_to_system_time(self.anchor(), offset),
+            })),
+            Err(Error::RegularEof) => None,
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<T: Payload> RecordedMessage<T> {
+    /// The message, stamped with an instant of this process that keeps the spacing between the
+    /// messages of the recording.
+    pub fn get_message(&self) -> &Message<T> {
+        &self.message
+    }
+
+    pub fn into_message(self) -> Message<T> {
+        self.message
+    }
+
+    /// Nanoseconds since [`RecordingReader::anchor`] on the monotonic clock of the recording
+    /// machine, negative for messages stamped before the recording started.
+    pub fn get_offset_nanos(&self) -> i64 {
+        self.offset
+    }
+
+    /// The anchor plus the offset.
+    pub fn get_wall_time(&self) -> SystemTime {
+        self.wall_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Channel;
+    use crate::private::test_tools::{TempFile, TestPayload};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_recording_reader() {
+        let temp_file = TempFile::new().unwrap();
+        let channel = Channel::<TestPayload>::new();
+
+        let before = SystemTime::now();
+        let recorder = channel.new_recorder(&temp_file).unwrap();
+        let after = SystemTime::now();
+
+        let now = Instant::now();
+        let time_stamps = [
+            now - Duration::from_secs(1), // older than the recording
+            now,
+            now + Duration::from_millis(1500),
+        ];
+        for (i, &time_stamp) in time_stamps.iter().enumerate() {
+            channel.push_message(Message::new(time_stamp, TestPayload::new(i)));
+        }
+        drop(recorder);
+
+        let reader = RecordingReader::<TestPayload>::open(&temp_file).unwrap();
+        let anchor = reader.anchor();
+        assert!(before <= anchor && anchor <= after);
+
+        let messages = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].get_offset_nanos() < 0);
+        assert!(messages[0].get_wall_time() < anchor);
+
+        for (i, message) in messages.iter().enumerate() {
+            message.get_message().get_payload().check(i);
+
+            let spacing = time_stamps[i] - time_stamps[0];
+            let offset = message.get_offset_nanos() - messages[0].get_offset_nanos();
+            assert_eq!(offset, spacing.as_nanos() as i64);
+            assert_eq!(
+                message.get_wall_time(),
+                messages[0].get_wall_time() + spacing
+            );
+            assert_eq!(
+                message.get_message().get_type_stamp(),
+                messages[0].get_message().get_type_stamp() + spacing
+            );
+        }
+    }
+
+    #[test]
+    fn test_recording_reader_error() {
+        let temp_file = TempFile::new().unwrap();
+        std::fs::write(temp_file.path(), b"not a recording").unwrap();
+
+        assert!(matches!(
+            RecordingReader::<TestPayload>::open(&temp_file),
+            Err(Error::BadHeader)
+        ));
+    }
+
+    #[test]
+    fn test_recording_reader_recovery() {
+        let temp_file = TempFile::new().unwrap();
+        let channel = Channel::<TestPayload>::new();
+        let recorder = channel.new_recorder(&temp_file).unwrap();
+        (0..10).for_each(|x| channel.push(TestPayload::new(x)));
+        drop(recorder);
+
+        // Damage the data of the last message, which ends where the index starts
+        let mut bytes = std::fs::read(temp_file.path()).unwrap();
+        let trailer = &bytes[bytes.len() - 12..];
+        let index_position = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+        bytes[index_position as usize - 1] ^= 0xff;
+        std::fs::write(temp_file.path(), &bytes).unwrap();
+
+        let reader = RecordingReader::<TestPayload>::open(&temp_file).unwrap();
+        let messages = reader.collect::<Vec<_>>();
+        assert_eq!(messages.len(), 10);
+        assert!(matches!(messages[9], Err(Error::Corrupted { .. })));
+
+        let mut reader = RecordingReader::<TestPayload>::open_recovering(&temp_file).unwrap();
+        let values = reader
+            .by_ref()
+            .map(|m| m.unwrap().get_message().get_payload().value())
+            .collect::<Vec<_>>();
+        assert_eq!(values, (0..9).collect::<Vec<_>>());
+        assert_eq!(reader.losses().len(), 1);
+        assert_eq!(reader.losses()[0].chunks, 10..11);
+        assert_eq!(reader.losses()[0].bytes.end, index_position);
+    }
+
+    #[test]
+    fn test_recording_reader_seek() {
+        let temp_file = TempFile::new().unwrap();
+        let channel = Channel::<TestPayload>::new();
+        let recorder = channel.new_recorder(&temp_file).unwrap();
+
+        let now = Instant::now();
+        for i in 0..300 {
+            let time_stamp = now + Duration::from_millis(10 * i as u64);
+            channel.push_message(Message::new(time_stamp, TestPayload::new(i)));
+        }
+        drop(recorder);
+
+        let mut reader = RecordingReader::<TestPayload>::open(&temp_file).unwrap();
+        let start = reader.next().unwrap().unwrap().get_wall_time();
+        let value = |reader: &mut RecordingReader<TestPayload>| {
+            reader
+                .next()
+                .map(|m| m.unwrap().get_message().get_payload().value())
+        };
+
+        reader
+            .seek_to_time(start + Duration::from_millis(2000))
+            .unwrap();
+        assert_eq!(value(&mut reader), Some(200));
+        reader
+            .seek_to_time(start + Duration::from_millis(1995))
+            .unwrap();
+        assert_eq!(value(&mut reader), Some(200));
+        reader.seek_to_time(start - Duration::from_secs(1)).unwrap();
+        assert_eq!(value(&mut reader), Some(0));
+        reader
+            .seek_to_time(start + Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(value(&mut reader), None);
+
+        reader.seek_to_index(129).unwrap();
+        assert_eq!(value(&mut reader), Some(129));
+        assert_eq!(value(&mut reader), Some(130));
+        reader.seek_to_index(1000).unwrap();
+        assert_eq!(value(&mut reader), None);
+
+        let bytes = std::fs::read(temp_file.path()).unwrap();
+        let mut reader = RecordingReader::<TestPayload>::from_read(
+            std::io::Cursor::new(bytes),
+            &ReadLimits::default(),
+        )
+        .unwrap();
+        assert!(matches!(reader.seek_to_index(0), Err(Error::NotSeekable)));
+    }
+
+    #[test]
+    fn test_recording_reader_limits() {
+        let temp_file = TempFile::new().unwrap();
+        let channel = Channel::<TestPayload>::new();
+        let recorder = channel.new_recorder(&temp_file).unwrap();
+        channel.push(TestPayload::new(0));
+        drop(recorder);
+
+        let bytes = std::fs::read(temp_file.path()).unwrap();
+        let format_name = TestPayload::format_name().len() as u64;
+        let open = |limits: ReadLimits| {
+            RecordingReader::<TestPayload>::from_read(std::io::Cursor::new(bytes.clone()), &limits)
+        };
+
+        match open(ReadLimits::new().max_format_name(format_name - 1)) {
+            Err(Error::FormatNameTooLong { size, limit }) => {
+                assert_eq!((size, limit), (format_name, format_name - 1))
+            }
+            _ => panic!("expected FormatNameTooLong"),
+        }
+
+        let mut reader = open(ReadLimits::new().max_chunk_size(0)).unwrap();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::ChunkTooLarge { limit: 0, .. }))
+        ));
+        assert!(reader.next().is_none());
+
+        let mut reader = open(ReadLimits::new().max_format_name(format_name)).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+    }
+}
@@ -0,0 +1,310 @@
+use crate::private::queue::{Hook, Queue};
+use crate::private::signal::Signal;
+use crate::{LatestSubscription, Payload, Subscription};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Waits on several subscriptions at once, possibly of different payload types, and tells which
+/// one has messages to pull.
+///
+/// Subscriptions are identified by the index returned by [`Select::add`]. A [`Subscription`]
+/// is only ready when its queue has messages, not when it holds messages already pulled by its
+/// iterator. A dropped subscription is never ready again, even if it had messages left.
+///
+/// The [`Waker`](crate::Waker) of any of the subscriptions cancels the current or next wait.
+pub struct Select {
+    sources: Vec<Option<SelectHandle>>, // None -> subscription dropped
+    signal: Arc<Signal>,
+    hook: Hook,
+    next: usize, // where to start looking, so that a busy subscription can't starve the others
+}
+
+/// Subscriptions that a [`Select`] can wait on.
+pub trait Selectable {
+    #[doc(hidden)]
+    fn select_handle(&self) -> SelectHandle;
+}
+
+#[doc(hidden)]
+pub struct SelectHandle {
+    source: Box<dyn SelectSource>,
+}
+
+impl Default for Select {
+    fn default() -> Self {
+        let signal = Arc::new(Signal::default());
+        let signal_for_hook = signal.clone();
+
+        Self {
+            sources: Vec::new(),
+            signal,
+            hook: Arc::new(move || signal_for_hook.raise()),
+            next: 0,
+        }
+    }
+}
+
+impl Select {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the index that identifies `subscription` in the results of this select.
+    pub fn add(&mut self, subscription: &impl Selectable) -> usize {
+        let handle = subscription.select_handle();
+        handle.source.add_hook(&self.hook);
+        self.sources.push(Some(handle));
+        self.sources.len() - 1
+    }
+
+    /// Returns the index of a subscription with messages to pull, without blocking.
+    pub fn try_select(&mut self) -> Option<usize> {
+        self.remove_closed();
+        let count = self.sources.len();
+
+        let ready = (0..count).map(|i| (self.next + i) % count).find(|&i| {
+            self.sources[i]
+                .as_ref()
+                .is_some_and(|h| h.source.is_ready())
+        });
+
+        if let Some(index) = ready {
+            self.next = index + 1;
+        }
+        ready
+    }
+
+    /// Blocks until a subscription has messages to pull and returns its index, or `None` if
+    /// cancelled by the [`Waker`](crate::Waker) of one of the subscriptions.
+    pub fn wait(&mut self) -> Option<usize> {
+        self.wait_until(None)
+    }
+
+    /// Like [`Select::wait`], but also returns `None` after `timeout`.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Option<usize> {
+        self.wait_until(Some(Instant::now() + timeout))
+    }
+
+    // Indices stay the same
+    fn remove_closed(&mut self) {
+        for source in &mut self.sources {
+            if let Some(handle) = source.take_if(|handle| handle.source.is_closed()) {
+                handle.source.remove_hook(&self.hook);
+            }
+        }
+    }
+
+    fn wait_until(&mut self, deadline: Option<Instant>) -> Option<usize> {
+        loop {
+            // Every wake-up is consumed, so that none cancels a later wait
+            let woken = self
+                .sources
+                .iter()
+                .flatten()
+                .filter(|h| h.source.take_woken());
+            if woken.count() > 0 {
+                return None;
+            }
+
+            if let Some(index) = self.try_select() {
+                return Some(index);
+            }
+
+            if !self.signal.wait(deadline) {
+                return None;
+            }
+        }
+    }
+}
+
+impl Drop for Select {
+    fn drop(&mut self) {
+        self.sources
+            .iter()
+            .flatten()
+            .for_each(|handle| handle.source.remove_hook(&self.hook));
+    }
+}
+
+impl SelectHandle {
+    pub(crate) fn new<T: Payload>(queue: &Queue<T>) -> Self {
+        Self {
+            source: Box::new(queue.clone()),
+        }
+    }
+}
+
+trait SelectSource: Send + Sync {
+    fn is_ready(&self) -> bool;
+    fn is_closed(&self) -> bool;
+    fn take_woken(&self) -> bool;
+    fn add_hook(&self, hook: &Hook);
+    fn remove_hook(&self, hook: &Hook);
+}
+
+impl<T: Payload> SelectSource for Queue<T> {
+    fn is_ready(&self) -> bool {
+        self.len() > 0 && !self.is_closed()
+    }
+
+    fn is_closed(&self) -> bool {
+        Queue::is_closed(self)
+    }
+
+    fn take_woken(&self) -> bool {
+        Queue::take_woken(self)
+    }
+
+    fn add_hook(&self, hook: &Hook) {
+        Queue::add_hook(self, hook);
+    }
+
+    fn remove_hook(&self, hook: &Hook) {
+        Queue::remove_hook(self, hook);
+    }
+}
+
+impl<T: Payload> Selectable for Subscription<T> {
+    fn select_handle(&self) -> SelectHandle {
+        SelectHandle::new(self.queue())
+    }
+}
+
+impl<T: Payload> Selectable for LatestSubscription<T> {
+    fn select_handle(&self) -> SelectHandle {
+        SelectHandle::new(self.queue())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private::test_tools::{EmptyPayload, TestPayload};
+    use crate::{AnyChannel, Channel};
+    use std::thread::sleep;
+
+    #[test]
+    fn test_select() {
+        let a = Channel::<TestPayload>::new();
+        let b = Channel::<EmptyPayload>::new();
+        let c = Channel::<TestPayload>::new();
+
+        let mut sub_a = a.subscribe();
+        let mut sub_b = b.subscribe_latest();
+        let mut sub_c = c.subscribe_bytes();
+
+        let mut select = Select::new();
+        assert_eq!(select.add(&sub_a), 0);
+        assert_eq!(select.add(&sub_b), 1);
+        assert_eq!(select.add(&sub_c), 2);
+
+        assert_eq!(select.try_select(), None);
+
+        b.push(EmptyPayload::default());
+        assert_eq!(select.wait(), Some(1));
+        sub_b.latest().unwrap();
+        assert_eq!(select.try_select(), None);
+
+        // Round robin
+        a.push(TestPayload::new(0));
+        c.push(TestPayload::new(0));
+        assert_eq!(select.wait(), Some(2));
+        assert_eq!(select.wait(), Some(0));
+        assert_eq!(select.wait(), Some(2));
+        assert_eq!(sub_a.try_pull().len(), 1);
+        assert_eq!(sub_c.try_pull().len(), 1);
+        assert_eq!(select.wait_timeout(Duration::from_millis(10)), None);
+    }
+
+    #[test]
+    fn test_select_wait() {
+        const WAIT_TIME: Duration = Duration::from_millis(100);
+
+        let a = Channel::<TestPayload>::new();
+        let b = Channel::<TestPayload>::new();
+        let mut select = Select::new();
+        let sub_a = a.subscribe();
+        let sub_b = b.subscribe();
+        select.add(&sub_a);
+        select.add(&sub_b);
+
+        let start_time = Instant::now();
+        let join_handle = std::thread::spawn(move || {
+            sleep(WAIT_TIME);
+            b.push(TestPayload::new(0));
+        });
+
+        assert_eq!(select.wait(), Some(1));
+        assert!(start_time.elapsed() >= WAIT_TIME);
+        join_handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_select_waker() {
+        const WAIT_TIME: Duration = Duration::from_millis(100);
+
+        let a = Channel::<TestPayload>::new();
+        let b = Channel::<TestPayload>::new();
+        let sub_a = a.subscribe();
+        let sub_b = b.subscribe_latest();
+        let mut select = Select::new();
+        select.add(&sub_a);
+        select.add(&sub_b);
+
+        // Either subscription's waker cancels the wait, once
+        sub_b.waker().wake_up();
+        assert_eq!(select.try_select(), None);
+        assert_eq!(select.wait(), None);
+        assert_eq!(select.wait_timeout(Duration::from_millis(10)), None);
+
+        let waker = sub_a.waker();
+        let start_time = Instant::now();
+        let join_handle = std::thread::spawn(move || {
+            sleep(WAIT_TIME);
+            waker.wake_up();
+        });
+
+        assert_eq!(select.wait(), None);
+        assert!(start_time.elapsed() >= WAIT_TIME);
+        join_handle.join().unwrap();
+
+        a.push(TestPayload::new(0));
+        assert_eq!(select.wait(), Some(0));
+    }
+
+    #[test]
+    fn test_select_dropped_subscription() {
+        let a = Channel::<TestPayload>::new();
+        let b = Channel::<TestPayload>::new();
+        let sub_a = a.subscribe();
+        let mut sub_b = b.subscribe();
+
+        let mut select = Select::new();
+        select.add(&sub_a);
+        select.add(&sub_b);
+
+        a.push(TestPayload::new(0));
+        drop(sub_a); // with a message left
+        for _ in 0..3 {
+            assert_eq!(select.wait_timeout(Duration::from_millis(10)), None);
+        }
+        assert_eq!(a.queues_len(), 0);
+
+        b.push(TestPayload::new(0));
+        assert_eq!(select.wait(), Some(1));
+        assert_eq!(sub_b.try_pull().len(), 1);
+    }
+
+    #[test]
+    fn test_drop_select() {
+        let a = Channel::<TestPayload>::new();
+        let sub_a = a.subscribe();
+
+        let mut select = Select::new();
+        select.add(&sub_a);
+        let signal = Arc::downgrade(&select.signal);
+        drop(select);
+
+        assert!(signal.upgrade().is_none()); // hooks removed
+    }
+}
@@ -0,0 +1,414 @@
+use crate::{Channel, Error, Message, Payload, ThreadedConsumer};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Request/response on top of a channel: requests are tagged with a correlation id, and every
+/// server answers every request it receives, straight to the caller. Clones share the same
+/// channel.
+///
+/// With several servers, [`Service::call`] returns the first response and
+/// [`Service::call_all_timeout`] collects them all. A handler that panics answers
+/// [`Error::HandlerPanicked`], and its server keeps serving.
+pub struct Service<Req: Payload, Resp: Payload> {
+    requests: Channel<Request<Req>>,
+    callers: Arc<Callers<Resp>>,
+}
+
+/// Answers the requests of a [`Service`] until dropped, created by [`Service::serve`].
+pub struct ServiceServer<Req: Payload> {
+    consumer: Option<ThreadedConsumer<Request<Req>>>,
+    callers: Arc<dyn ServerGone>,
+}
+
+/// Response to a call, dereferencing to the response payload.
+pub struct Reply<Resp: Payload> {
+    message: Message<Resp>,
+}
+
+#[derive(bincode::Encode, bincode::Decode)]
+struct Request<Req> {
+    id: u64,
+    payload: Req,
+}
+
+impl<Req: Payload> Payload for Request<Req> {}
+
+// The pending calls, by request id
+struct Callers<Resp: Payload> {
+    pending: Mutex<HashMap<u64, Sender<Answer<Resp>>>>,
+    next_id: AtomicU64,
+}
+
+enum Answer<Resp: Payload> {
+    Reply(Message<Resp>),
+    Panicked,
+    ServerGone, // one of them, after it answered what it could
+}
+
+// Removes its call from the pending ones when dropped
+struct PendingCall<'a, Resp: Payload> {
+    callers: &'a Callers<Resp>,
+    id: u64,
+    answers: Receiver<Answer<Resp>>,
+}
+
+// Lets ServiceServer wake the callers without knowing the response type
+trait ServerGone: Send + Sync {
+    fn server_gone(&self);
+}
+
+impl<Req: Payload, Resp: Payload> Clone for Service<Req, Resp> {
+    fn clone(&self) -> Self {
+        Self {
+            requests: self.requests.clone(),
+            callers: self.callers.clone(),
+        }
+    }
+}
+
+impl<Req: Payload, Resp: Payload> Default for Service<Req, Resp> {
+    fn default() -> Self {
+        Self {
+            requests: Channel::new(),
+            callers: Arc::new(Callers {
+                pending: Mutex::new(HashMap::new()),
+                next_id: AtomicU64::new(0),
+            }),
+        }
+    }
+}
+
+impl<Req: Payload, Resp: Payload> Service<Req, Resp> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers requests with `handler`, on a dedicated thread, until the server is dropped.
+    pub fn serve(
+        &self,
+        mut handler: impl FnMut(&Req) -> Resp + Send + 'static,
+    ) -> ServiceServer<Req> {
+        let callers = self.callers.clone();
+
+        let consumer = self.requests.new_threaded_consumer(move |messages| {
+            for message in messages {
+                let request = message.get_payload();
+                let answer = match std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    handler(&request.payload)
+                })) {
+                    Ok(payload) => Answer::Reply(Message::new(Instant::now(), payload)),
+                    Err(_) => Answer::Panicked, // already reported by the panic hook
+                };
+                callers.answer(request.id, answer);
+            }
+        });
+
+        ServiceServer {
+            consumer: Some(consumer),
+            callers: self.callers.clone(),
+        }
+    }
+
+    pub fn servers(&self) -> usize {
+        self.requests.queues_len()
+    }
+
+    /// Blocks until a server answers `request`.
+    /// Fails with [`Error::NoServer`] if there is no server, or none left to answer, and with
+    /// [`Error::HandlerPanicked`] if the first server to answer panicked.
+    pub fn call(&self, request: Req) -> Result<Reply<Resp>, Error> {
+        self.call_until(request, None)
+    }
+
+    /// Like [`Service::call`], but fails with [`Error::Timeout`] after `timeout`.
+    pub fn call_timeout(&self, request: Req, timeout: Duration) -> Result<Reply<Resp>, Error> {
+        self.call_until(request, Some(Instant::now() + timeout))
+    }
+
+    /// Returns the responses of all the servers there were when called, in the order they
+    /// arrived, or those received so far after `timeout`. Servers that panicked don't respond.
+    /// Fails with [`Error::NoServer`] if there is no server.
+    pub fn call_all_timeout(
+        &self,
+        request: Req,
+        timeout: Duration,
+    ) -> Result<Vec<Reply<Resp>>, Error> {
+        let deadline = Instant::now() + timeout;
+        let servers = self.servers();
+        let mut replies = Vec::new();
+        let mut answered = 0;
+
+        let call = self.send(request)?;
+        while answered < servers {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match call.answers.recv_timeout(timeout) {
+                Ok(Answer::Reply(message)) => replies.push(Reply { message }),
+                Ok(Answer::Panicked) => {}
+                Ok(Answer::ServerGone) if self.servers() == 0 => {
+                    replies.extend(call.answers.try_iter().filter_map(|answer| match answer {
+                        Answer::Reply(message) => Some(Reply { message }),
+                        _ => None,
+                    }));
+                    break;
+                }
+                Ok(Answer::ServerGone) => continue,
+                Err(_) => break,
+            }
+            answered += 1;
+        }
+
+        Ok(replies)
+    }
+
+    fn call_until(&self, request: Req, deadline: Option<Instant>) -> Result<Reply<Resp>, Error> {
+        let call = self.send(request)?;
+
+        loop {
+            let answer = match deadline {
+                None => call
+                    .answers
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+                Some(deadline) => call
+                    .answers
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            };
+
+            match answer {
+                Ok(Answer::Reply(message)) => return Ok(Reply { message }),
+                Ok(Answer::Panicked) => return Err(Error::HandlerPanicked),
+                // Servers answer before going away: once none is left, what they sent is queued
+                Ok(Answer::ServerGone) if self.servers() == 0 => {
+                    return match call
+                        .answers
+                        .try_iter()
+                        .find(|answer| !matches!(answer, Answer::ServerGone))
+                    {
+                        Some(Answer::Reply(message)) => Ok(Reply { message }),
+                        Some(_) => Err(Error::HandlerPanicked),
+                        None => Err(Error::NoServer),
+                    };
+                }
+                Ok(Answer::ServerGone) => continue,
+                Err(RecvTimeoutError::Timeout) => return Err(Error::Timeout),
+                Err(RecvTimeoutError::Disconnected) => return Err(Error::NoServer),
+            }
+        }
+    }
+
+    // Registers the call before pushing, so that no answer can be missed
+    fn send(&self, request: Req) -> Result<PendingCall<'_, Resp>, Error> {
+        let (sender, answers) = std::sync::mpsc::channel();
+        let id = self.callers.next_id.fetch_add(1, Ordering::Relaxed);
+        self.callers.pending.lock().unwrap().insert(id, sender);
+        let call = PendingCall {
+            callers: &self.callers,
+            id,
+            answers,
+        };
+
+        if self.servers() == 0 {
+            return Err(Error::NoServer);
+        }
+
+        self.requests.push(Request {
+            id,
+            payload: request,
+        });
+
+        Ok(call)
+    }
+}
+
+impl<Resp: Payload> Callers<Resp> {
+    fn answer(&self, id: u64, answer: Answer<Resp>) {
+        if let Some(sender) = self.pending.lock().unwrap().get(&id) {
+            let _ = sender.send(answer); // the caller may have timed out
+        }
+    }
+}
+
+impl<Resp: Payload> ServerGone for Callers<Resp> {
+    fn server_gone(&self) {
+        for sender in self.pending.lock().unwrap().values() {
+            let _ = sender.send(Answer::ServerGone);
+        }
+    }
+}
+
+impl<Resp: Payload> Drop for PendingCall<'_, Resp> {
+    fn drop(&mut self) {
+        self.callers.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl<Req: Payload> Drop for ServiceServer<Req> {
+    fn drop(&mut self) {
+        self.consumer.take(); // stops answering, and is no longer counted
+        self.callers.server_gone();
+    }
+}
+
+impl<Resp: Payload> Reply<Resp> {
+    pub fn get_type_stamp(&self) -> Instant {
+        self.message.get_type_stamp()
+    }
+
+    pub fn get_payload(&self) -> &Resp {
+        self.message.get_payload()
+    }
+}
+
+impl<Resp: Payload> Deref for Reply<Resp> {
+    type Target = Resp;
+
+    fn deref(&self) -> &Self::Target {
+        self.get_payload()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private::test_tools::TestPayload;
+    use std::thread::sleep;
+
+    #[test]
+    fn test_service() {
+        let service = Service::<TestPayload, TestPayload>::new();
+        let _server = service.serve(|request| TestPayload::new(request.value() * 2));
+        assert_eq!(service.servers(), 1);
+
+        let client = service.clone();
+        let join_handle = std::thread::spawn(move || {
+            (0..100).for_each(|x| {
+                let reply = client.call(TestPayload::new(x)).unwrap();
+                assert_eq!(reply.value(), x * 2);
+            });
+        });
+
+        (100..200).for_each(|x| {
+            let reply = service.call(TestPayload::new(x)).unwrap();
+            assert_eq!(reply.get_payload().value(), x * 2);
+        });
+
+        join_handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_service_no_server() {
+        let service = Service::<TestPayload, TestPayload>::new();
+        assert!(matches!(
+            service.call(TestPayload::new(0)),
+            Err(Error::NoServer)
+        ));
+
+        let server = service.serve(|request| TestPayload::new(request.value()));
+        assert!(service.call(TestPayload::new(0)).is_ok());
+
+        drop(server);
+        assert!(matches!(
+            service.call_all_timeout(TestPayload::new(0), Duration::ZERO),
+            Err(Error::NoServer)
+        ));
+    }
+
+    #[test]
+    fn test_service_server_gone() {
+        let service = Service::<TestPayload, TestPayload>::new();
+        let server = service.serve(|request| {
+            sleep(Duration::from_millis(200));
+            TestPayload::new(request.value())
+        });
+
+        let calls = (0..2)
+            .map(|x| {
+                let client = service.clone();
+                let join_handle = std::thread::spawn(move || client.call(TestPayload::new(x)));
+                sleep(Duration::from_millis(50)); // the first request is being processed
+                join_handle
+            })
+            .collect::<Vec<_>>();
+
+        drop(server); // completes the first request only
+        let results = calls
+            .into_iter()
+            .map(|join_handle| join_handle.join().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(results[0].as_ref().unwrap().value(), 0);
+        assert!(matches!(results[1], Err(Error::NoServer)));
+    }
+
+    #[test]
+    fn test_service_handler_panic() {
+        let service = Service::<TestPayload, TestPayload>::new();
+        let server = service.serve(|request| match request.value() {
+            0 => panic!("Test panic"),
+            x => TestPayload::new(x),
+        });
+
+        assert!(matches!(
+            service.call(TestPayload::new(0)),
+            Err(Error::HandlerPanicked)
+        ));
+        assert!(
+            service
+                .call_all_timeout(TestPayload::new(0), Duration::from_secs(5))
+                .unwrap()
+                .is_empty()
+        );
+
+        // Still serving, and dropped without panicking
+        assert_eq!(service.call(TestPayload::new(1)).unwrap().value(), 1);
+        drop(server);
+    }
+
+    #[test]
+    fn test_service_timeout() {
+        const WAIT_TIME: Duration = Duration::from_millis(100);
+
+        let service = Service::<TestPayload, TestPayload>::new();
+        let _server = service.serve(|request| {
+            sleep(WAIT_TIME * 2);
+            TestPayload::new(request.value())
+        });
+
+        let start_time = Instant::now();
+        assert!(matches!(
+            service.call_timeout(TestPayload::new(0), WAIT_TIME),
+            Err(Error::Timeout)
+        ));
+        assert!(start_time.elapsed() >= WAIT_TIME);
+
+        // The late response to the first call is not mistaken for this one
+        let reply = service
+            .call_timeout(TestPayload::new(1), WAIT_TIME * 5)
+            .unwrap();
+        assert_eq!(reply.value(), 1);
+    }
+
+    #[test]
+    fn test_service_servers() {
+        let service = Service::<TestPayload, TestPayload>::new();
+        let _servers = (0..3)
+            .map(|i| service.serve(move |request| TestPayload::new(request.value() + i)))
+            .collect::<Vec<_>>();
+
+        let replies = service
+            .call_all_timeout(TestPayload::new(10), Duration::from_secs(5))
+            .unwrap();
+
+        let mut values = replies
+            .iter()
+            .map(|reply| reply.value())
+            .collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, [10, 11, 12]);
+    }
+}
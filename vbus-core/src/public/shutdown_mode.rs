@@ -0,0 +1,8 @@
+/// How a [`ThreadedConsumer`](crate::ThreadedConsumer) or a [`Recorder`](crate::Recorder) stops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownMode {
+    /// Process every message pushed before the shutdown, then stop.
+    Drain,
+    /// Stop after the batch being processed, discarding queued messages.
+    Immediate,
+}
@@ -0,0 +1,215 @@
+use crate::private::Consumer;
+use crate::private::queue::{Queue, Waker};
+use crate::{Message, Payload};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Pull-based reader of a [`Channel`](crate::Channel), created by
+/// [`Channel::subscribe`](crate::Channel::subscribe).
+///
+/// Messages pushed after the subscription was created are queued until pulled. Iterating over a
+/// subscription blocks until the next message is available.
+pub struct Subscription<T: Payload> {
+    consumer: Consumer<T>,
+    pending: VecDeque<Message<T>>, // pulled from the queue but not yet returned by next()
+}
+
+impl<T: Payload> Subscription<T> {
+    pub(crate) fn new(consumer: Consumer<T>) -> Self {
+        Self {
+            consumer,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Returns all queued messages without blocking, possibly none.
+    pub fn try_pull(&mut self) -> Vec<Message<T>> {
+        self.with_pending(self.consumer.pull())
+    }
+
+    /// Blocks until at least one message is available, then returns all queued messages.
+    pub fn wait_pull(&mut self) -> Vec<Message<T>> {
+        if !self.pending.is_empty() {
+            return self.try_pull();
+        }
+
+        loop {
+            let messages = self.consumer.wait_pull();
+            if !messages.is_empty() {
+                return messages;
+            }
+        }
+    }
+
+    /// Like [`Subscription::wait_pull`], but returns an empty vector after `timeout`.
+    pub fn wait_pull_timeout(&mut self, timeout: Duration) -> Vec<Message<T>> {
+        if !self.pending.is_empty() {
+            return self.try_pull();
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let messages = self
+                .consumer
+                .wait_pull_timeout(deadline.saturating_duration_since(Instant::now()));
+            if !messages.is_empty() || Instant::now() >= deadline {
+                return messages;
+            }
+        }
+    }
+
+    /// Returns a handle that cancels a [`Select`](crate::Select) waiting on this subscription.
+    pub fn waker(&self) -> Waker<T> {
+        self.queue().waker()
+    }
+
+    /// Number of messages discarded by the queue overflow policy.
+    pub fn dropped(&self) -> usize {
+        self.consumer.queue().dropped()
+    }
+
+    pub(crate) fn queue(&self) -> &Queue<T> {
+        self.consumer.queue()
+    }
+
+    fn with_pending(&mut self, messages: Vec<Message<T>>) -> Vec<Message<T>> {
+        if self.pending.is_empty() {
+            return messages;
+        }
+
+        let mut output: Vec<Message<T>> = self.pending.drain(..).collect();
+        output.extend(messages);
+        output
+    }
+}
+
+impl<T: Payload> Iterator for Subscription<T> {
+    type Item = Message<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pending.is_empty() {
+            let messages = self.wait_pull();
+            self.pending.extend(messages);
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::private::test_tools::TestPayload;
+    use crate::{Channel, OverflowPolicy};
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_try_pull() {
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe();
+
+        assert!(subscription.try_pull().is_empty());
+
+        (0..3).for_each(|x| channel.push(TestPayload::new(x)));
+        let messages = subscription.try_pull();
+        assert_eq!(messages.len(), 3);
+        for (i, message) in messages.iter().enumerate() {
+            message.get_payload().check(i);
+        }
+
+        assert!(subscription.try_pull().is_empty());
+    }
+
+    #[test]
+    fn test_wait_pull() {
+        const WAIT_TIME: Duration = Duration::from_millis(200);
+
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe();
+
+        let channel_for_thread = channel.clone();
+        let start_time = Instant::now();
+        let join_handle = std::thread::spawn(move || {
+            sleep(WAIT_TIME);
+            channel_for_thread.push(TestPayload::new(42));
+        });
+
+        let messages = subscription.wait_pull();
+        assert!(start_time.elapsed() >= WAIT_TIME);
+        assert_eq!(messages.len(), 1);
+        messages[0].get_payload().check(42);
+
+        join_handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_wait_pull_timeout() {
+        const WAIT_TIME: Duration = Duration::from_millis(200);
+
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe();
+
+        let start_time = Instant::now();
+        assert!(subscription.wait_pull_timeout(WAIT_TIME).is_empty());
+        assert!(start_time.elapsed() >= WAIT_TIME);
+
+        channel.push(TestPayload::new(42));
+        let messages = subscription.wait_pull_timeout(WAIT_TIME);
+        assert_eq!(messages.len(), 1);
+    }
+
+    #[test]
+    fn test_iterator() {
+        let channel = Channel::<TestPayload>::new();
+        let subscription = channel.subscribe();
+
+        let channel_for_thread = channel.clone();
+        let join_handle = std::thread::spawn(move || {
+            for i in 0..10 {
+                channel_for_thread.push(TestPayload::new(i));
+                sleep(Duration::from_millis(10));
+            }
+        });
+
+        let values: Vec<usize> = subscription
+            .take(10)
+            .map(|m| m.get_payload().value())
+            .collect();
+        assert_eq!(values, (0..10).collect::<Vec<_>>());
+
+        join_handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_iterator_then_pull() {
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe();
+
+        (0..3).for_each(|x| channel.push(TestPayload::new(x)));
+        subscription.next().unwrap().get_payload().check(0);
+
+        channel.push(TestPayload::new(3));
+        let values: Vec<usize> = subscription
+            .try_pull()
+            .iter()
+            .map(|m| m.get_payload().value())
+            .collect();
+        assert_eq!(values, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_bounded_subscription() {
+        let channel = Channel::<TestPayload>::new();
+        let mut subscription = channel.subscribe_bounded(2, OverflowPolicy::DropOldest);
+
+        (0..5).for_each(|x| channel.push(TestPayload::new(x)));
+        assert_eq!(subscription.dropped(), 3);
+
+        let values: Vec<usize> = subscription
+            .try_pull()
+            .iter()
+            .map(|m| m.get_payload().value())
+            .collect();
+        assert_eq!(values, [3, 4]);
+    }
+}
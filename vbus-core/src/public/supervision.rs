@@ -0,0 +1,14 @@
+/// What a [`ThreadedConsumer`](crate::ThreadedConsumer) does when its `process` closure panics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Supervision {
+    /// Stop processing, and resume the panic when the consumer is dropped.
+    Propagate,
+    /// Drop the batch being processed and carry on. The panic is reported by the panic hook, as
+    /// usual, and to [`ConsumerConfig::on_panic`](crate::ConsumerConfig::on_panic) if set.
+    #[default]
+    LogAndSkip,
+    /// Like `LogAndSkip`, but also rebuild the closure from the factory given to
+    /// [`Channel::new_supervised_consumer`](crate::Channel::new_supervised_consumer). Consumers
+    /// created from a single closure keep using it.
+    Restart,
+}
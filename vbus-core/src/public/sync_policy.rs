@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+/// How a [`Synchronizer`](crate::Synchronizer) matches messages across channels.
+///
+/// `queue_size` is how many unmatched messages are kept per channel, the oldest ones being
+/// dropped first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Match messages with identical time stamps.
+    Exact { queue_size: usize },
+    /// Match messages whose time stamps are at most `tolerance` apart.
+    Approximate {
+        tolerance: Duration,
+        queue_size: usize,
+    },
+}
+
+impl SyncPolicy {
+    pub(crate) fn tolerance(&self) -> Duration {
+        match self {
+            SyncPolicy::Exact { .. } => Duration::ZERO,
+            SyncPolicy::Approximate { tolerance, .. } => *tolerance,
+        }
+    }
+
+    pub(crate) fn queue_size(&self) -> usize {
+        match self {
+            SyncPolicy::Exact { queue_size } => *queue_size,
+            SyncPolicy::Approximate { queue_size, .. } => *queue_size,
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use crate::private::Consumer;
+use crate::private::queue::Hook;
+use crate::private::signal::Signal;
+use crate::{Channel, Message, Payload, SyncPolicy};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/*
+ * Matching: the pivot is the latest of the oldest buffered messages. Since it never goes back in
+ * time, messages older than pivot - tolerance can never be matched and are dropped. Each channel
+ * then contributes its message closest to the pivot, the earlier one on a tie, which is only known
+ * for sure once a message at or after the pivot has arrived. The exact policy is a zero tolerance.
+ */
+
+/// Joins 2 to 6 channels into tuples of messages with matching time stamps, as described by a
+/// [`SyncPolicy`]. Created from a tuple of channel references, e.g.
+/// `Synchronizer::new((&camera, &imu), policy)` returns `(Message<Frame>, Message<Imu>)` tuples.
+pub struct Synchronizer<O> {
+    inputs: Box<dyn Inputs<Output = O>>,
+    policy: SyncPolicy,
+    signal: Arc<Signal>,
+    dropped: usize,
+}
+
+/// Tuples of channels that a [`Synchronizer`] can join.
+pub trait Synchronize {
+    type Output;
+
+    fn synchronize(self, policy: SyncPolicy) -> Synchronizer<Self::Output>;
+}
+
+impl<O> Synchronizer<O> {
+    /// Panics if the queue size of `policy` is 0.
+    pub fn new(channels: impl Synchronize<Output = O>, policy: SyncPolicy) -> Self {
+        channels.synchronize(policy)
+    }
+
+    fn with_inputs(inputs: Box<dyn Inputs<Output = O>>, policy: SyncPolicy) -> Self {
+        assert!(
+            policy.queue_size() > 0,
+            "sync queue size must be at least 1"
+        );
+
+        let signal = Arc::new(Signal::default());
+        let signal_for_hook = signal.clone();
+        let hook: Hook = Arc::new(move || signal_for_hook.raise());
+        inputs.add_hook(&hook);
+
+        Self {
+            inputs,
+            policy,
+            signal,
+            dropped: 0,
+        }
+    }
+
+    /// Returns the tuples matched so far without blocking, possibly none.
+    pub fn try_pull(&mut self) -> Vec<O> {
+        self.sync()
+    }
+
+    /// Blocks until at least one tuple is matched, then returns all of them.
+    pub fn wait_pull(&mut self) -> Vec<O> {
+        self.wait_until(None)
+    }
+
+    /// Like [`Synchronizer::wait_pull`], but returns an empty vector after `timeout`.
+    pub fn wait_pull_timeout(&mut self, timeout: Duration) -> Vec<O> {
+        self.wait_until(Some(Instant::now() + timeout))
+    }
+
+    /// Number of messages that could not be matched, or didn't fit in the queues.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    fn wait_until(&mut self, deadline: Option<Instant>) -> Vec<O> {
+        loop {
+            let output = self.sync();
+            if !output.is_empty() || !self.signal.wait(deadline) {
+                return output;
+            }
+        }
+    }
+
+    fn sync(&mut self) -> Vec<O> {
+        let count = self.inputs.count();
+        let tolerance = self.policy.tolerance();
+
+        for i in 0..count {
+            self.dropped += self.inputs.input(i).pull(self.policy.queue_size());
+        }
+
+        let mut output = Vec::new();
+
+        'matching: loop {
+            let mut pivot = None;
+            for i in 0..count {
+                match self.inputs.input(i).time(0) {
+                    None => break 'matching,
+                    Some(t) => pivot = pivot.max(Some(t)),
+                }
+            }
+            let pivot = pivot.unwrap();
+
+            let mut pruned = false;
+            for i in 0..count {
+                let input = self.inputs.input(i);
+                while input.time(0).is_some_and(|t| t + tolerance < pivot) {
+                    input.pop_front();
+                    self.dropped += 1;
+                    pruned = true;
+                }
+            }
+            if pruned {
+                continue; // new pivot
+            }
+
+            let mut candidates = Vec::with_capacity(count);
+            for i in 0..count {
+                let input = self.inputs.input(i);
+                let Some(after) = (0..input.len()).find(|&j| input.time(j).unwrap() >= pivot)
+                else {
+                    break 'matching; // a closer message may still come
+                };
+
+                // The message before the pivot is within tolerance, since older ones were pruned
+                let late = input.time(after).unwrap() - pivot;
+                let early = after.checked_sub(1).map(|j| pivot - input.time(j).unwrap());
+                match early {
+                    Some(early) if early <= late || late > tolerance => candidates.push(after - 1),
+                    _ => candidates.push(after),
+                }
+            }
+
+            for (i, candidate) in candidates.into_iter().enumerate() {
+                let input = self.inputs.input(i);
+                (0..candidate).for_each(|_| input.pop_front());
+                self.dropped += candidate;
+            }
+
+            output.push(self.inputs.take());
+        }
+
+        output
+    }
+}
+
+// The typed side, one per channel
+struct Input<T: Payload> {
+    consumer: Consumer<T>,
+    buffer: VecDeque<Message<T>>,
+}
+
+impl<T: Payload> Input<T> {
+    fn new(channel: &Channel<T>) -> Self {
+        Self {
+            consumer: channel.new_consumer(),
+            buffer: VecDeque::new(),
+        }
+    }
+}
+
+trait AnyInput {
+    // Returns the number of messages dropped to fit in queue_size
+    fn pull(&mut self, queue_size: usize) -> usize;
+    fn len(&self) -> usize;
+    fn time(&self, index: usize) -> Option<Instant>;
+    fn pop_front(&mut self);
+    fn add_hook(&self, hook: &Hook);
+}
+
+impl<T: Payload> AnyInput for Input<T> {
+    fn pull(&mut self, queue_size: usize) -> usize {
+        self.buffer.extend(self.consumer.pull());
+
+        let excess = self.buffer.len().saturating_sub(queue_size);
+        self.buffer.drain(..excess);
+        excess
+    }
+
+    fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn time(&self, index: usize) -> Option<Instant> {
+        self.buffer.get(index).map(|m| m.get_type_stamp())
+    }
+
+    fn pop_front(&mut self) {
+        self.buffer.pop_front();
+    }
+
+    fn add_hook(&self, hook: &Hook) {
+        self.consumer.queue().add_hook(hook);
+    }
+}
+
+trait Inputs: Send {
+    type Output;
+
+    fn count(&self) -> usize;
+    fn input(&mut self, index: usize) -> &mut dyn AnyInput;
+    fn take(&mut self) -> Self::Output; // pops the front message of each input
+    fn add_hook(&self, hook: &Hook);
+}
+
+macro_rules! synchronize_tuple {
+    ($($t:ident $i:tt),+) => {
+        impl<$($t: Payload),+> Synchronize for ($(&Channel<$t>,)+) {
+            type Output = ($(Message<$t>,)+);
+
+            fn synchronize(self, policy: SyncPolicy) -> Synchronizer<Self::Output> {
+                Synchronizer::with_inputs(Box::new(($(Input::new(self.$i),)+)), policy)
+            }
+        }
+
+        impl<$($t: Payload),+> Inputs for ($(Input<$t>,)+) {
+            type Output = ($(Message<$t>,)+);
+
+            fn count(&self) -> usize {
+                [$($i),+].len()
+            }
+
+            fn input(&mut self, index: usize) -> &mut dyn AnyInput {
+                match index {
+                    $($i => &mut self.$i,)+
+                    _ => unreachable!(),
+                }
+            }
+
+            fn take(&mut self) -> Self::Output {
+                ($(self.$i.buffer.pop_front().unwrap(),)+)
+            }
+
+            fn add_hook(&self, hook: &Hook) {
+                $(self.$i.add_hook(hook);)+
+            }
+        }
+    };
+}
+
+synchronize_tuple!(A 0, B 1);
+synchronize_tuple!(A 0, B 1, C 2);
+synchronize_tuple!(A 0, B 1, C 2, D 3);
+synchronize_tuple!(A 0, B 1, C 2, D 3, E 4);
+synchronize_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private::test_tools::{EmptyPayload, TestPayload};
+
+    fn push_at(channel: &Channel<TestPayload>, start: Instant, millis: u64) {
+        let time_stamp = start + Duration::from_millis(millis);
+        channel.push_message(Message::new(time_stamp, TestPayload::new(millis as usize)));
+    }
+
+    fn values(pairs: &[(Message<TestPayload>, Message<TestPayload>)]) -> Vec<(usize, usize)> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.get_payload().value(), b.get_payload().value()))
+            .collect()
+    }
+
+    #[test]
+    fn test_exact() {
+        let (a, b) = (Channel::new(), Channel::new());
+        let mut synchronizer = Synchronizer::new((&a, &b), SyncPolicy::Exact { queue_size: 10 });
+        let start = Instant::now();
+
+        [0, 10, 20, 30].iter().for_each(|&t| push_at(&a, start, t));
+        [5, 10, 15, 30].iter().for_each(|&t| push_at(&b, start, t));
+
+        assert_eq!(values(&synchronizer.try_pull()), [(10, 10), (30, 30)]);
+        assert_eq!(synchronizer.dropped(), 4);
+        assert!(synchronizer.try_pull().is_empty());
+    }
+
+    #[test]
+    fn test_approximate() {
+        let (a, b) = (Channel::new(), Channel::new());
+        let policy = SyncPolicy::Approximate {
+            tolerance: Duration::from_millis(5),
+            queue_size: 10,
+        };
+        let mut synchronizer = Synchronizer::new((&a, &b), policy);
+        let start = Instant::now();
+
+        [0, 10, 20, 30].iter().for_each(|&t| push_at(&a, start, t));
+        [2, 11, 13, 29].iter().for_each(|&t| push_at(&b, start, t));
+
+        assert_eq!(values(&synchronizer.try_pull()), [(0, 2), (10, 11)]);
+
+        // (30, 29) needs to know that nothing closer than 29 will come on b
+        push_at(&b, start, 40);
+        assert_eq!(values(&synchronizer.try_pull()), [(30, 29)]);
+    }
+
+    #[test]
+    fn test_approximate_closest() {
+        let (a, b) = (Channel::new(), Channel::new());
+        let policy = SyncPolicy::Approximate {
+            tolerance: Duration::from_millis(5),
+            queue_size: 10,
+        };
+        let mut synchronizer = Synchronizer::new((&a, &b), policy);
+        let start = Instant::now();
+
+        push_at(&a, start, 10);
+        [6, 11].iter().for_each(|&t| push_at(&b, start, t));
+
+        // 11 is closer to 10 than 6, though after it
+        assert_eq!(values(&synchronizer.try_pull()), [(10, 11)]);
+        assert_eq!(synchronizer.dropped(), 1);
+    }
+
+    #[test]
+    fn test_queue_size() {
+        let (a, b) = (Channel::new(), Channel::new());
+        let mut synchronizer = Synchronizer::new((&a, &b), SyncPolicy::Exact { queue_size: 2 });
+        let start = Instant::now();
+
+        (0..5).for_each(|t| push_at(&a, start, t));
+        assert!(synchronizer.try_pull().is_empty());
+        assert_eq!(synchronizer.dropped(), 3);
+
+        (0..5).for_each(|t| push_at(&b, start, t));
+        assert_eq!(values(&synchronizer.try_pull()), [(3, 3), (4, 4)]);
+        assert_eq!(synchronizer.dropped(), 6);
+    }
+
+    #[test]
+    fn test_wait_pull() {
+        const WAIT_TIME: Duration = Duration::from_millis(100);
+
+        let (a, b, c) = (
+            Channel::new(),
+            Channel::new(),
+            Channel::<EmptyPayload>::new(),
+        );
+        let policy = SyncPolicy::Exact { queue_size: 10 };
+        let mut synchronizer = Synchronizer::new((&a, &b, &c), policy);
+        let start = Instant::now();
+
+        push_at(&a, start, 0);
+        push_at(&b, start, 0);
+        assert!(synchronizer.wait_pull_timeout(WAIT_TIME).is_empty());
+        assert!(start.elapsed() >= WAIT_TIME);
+
+        let join_handle = std::thread::spawn(move || {
+            std::thread::sleep(WAIT_TIME);
+            c.push_message(Message::new(start, EmptyPayload::default()));
+        });
+
+        let triplets = synchronizer.wait_pull();
+        join_handle.join().unwrap();
+
+        assert_eq!(triplets.len(), 1);
+        assert_eq!(triplets[0].0.get_type_stamp(), start);
+        assert_eq!(triplets[0].2.get_type_stamp(), start);
+    }
+}
@@ -0,0 +1,332 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{JoinHandle, ThreadId, spawn};
+
+thread_local! {
+    // Pool whose worker is the current thread, if any
+    static CURRENT_POOL: Cell<*const PoolShared> = const { Cell::new(std::ptr::null()) };
+}
+
+/// Fixed set of worker threads shared by many consumers.
+///
+/// Each consumer runs on at most one worker at a time, so messages are still processed in order.
+/// Workers are stopped and joined once the pool and every consumer using it are dropped. Consumers
+/// can be dropped from a task of the same pool, even with a single worker.
+///
+/// A consumer blocked in its `process` closure holds a worker: with an
+/// [`OverflowPolicy::Block`](crate::OverflowPolicy::Block) queue between two pooled consumers,
+/// make sure the pool has enough workers.
+#[derive(Clone)]
+pub struct ThreadPool {
+    pool_handle: Arc<PoolHandle>,
+}
+
+impl ThreadPool {
+    /// Spawns `workers` threads. Panics if `workers` is 0.
+    pub fn new(workers: usize) -> Self {
+        assert!(workers > 0, "thread pool needs at least one worker");
+
+        let shared = Arc::new(PoolShared {
+            state: Mutex::new(PoolState {
+                ready: VecDeque::new(),
+                stopped: false,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        let join_handles = (0..workers)
+            .map(|_| {
+                let shared = shared.clone();
+                spawn(move || shared.work())
+            })
+            .collect();
+
+        Self {
+            pool_handle: Arc::new(PoolHandle {
+                shared,
+                join_handles,
+            }),
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.pool_handle.join_handles.len()
+    }
+
+    pub(crate) fn new_task(&self, job: Job) -> Arc<PoolTask> {
+        Arc::new(PoolTask {
+            pool: self.pool_handle.shared.clone(),
+            state: Mutex::new(TaskState::Idle),
+            condvar: Condvar::new(),
+            job: Mutex::new(Some(job)),
+        })
+    }
+}
+
+pub(crate) type Job = Box<dyn FnMut() + Send>;
+
+// Unit of work that runs on one worker at a time, as many times as it is scheduled
+pub(crate) struct PoolTask {
+    pool: Arc<PoolShared>,
+    state: Mutex<TaskState>,
+    condvar: Condvar,
+    job: Mutex<Option<Job>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TaskState {
+    Idle,
+    Scheduled,
+    Running { again: bool, thread: ThreadId },
+    Cancelled,
+}
+
+impl PoolTask {
+    pub fn schedule(self: &Arc<Self>) {
+        let mut state = self.lock();
+
+        match *state {
+            TaskState::Idle => {
+                *state = TaskState::Scheduled;
+                drop(state);
+                self.pool.submit(self.clone());
+            }
+            TaskState::Running { thread, .. } => {
+                *state = TaskState::Running {
+                    again: true,
+                    thread,
+                }
+            }
+            TaskState::Scheduled | TaskState::Cancelled => {}
+        }
+    }
+
+    // Waits for the current run to complete and returns the job, which won't run anymore. A
+    // scheduled run is dropped rather than waited for: it may be queued behind the caller's own.
+    // From the job itself, returns None and the job is dropped once it returns.
+    pub fn cancel(&self) -> Option<Job> {
+        let mut state = self.lock();
+
+        while let TaskState::Running { thread, .. } = *state {
+            if thread == std::thread::current().id() {
+                *state = TaskState::Cancelled;
+                return None;
+            }
+            state = self.condvar.wait(state).unwrap();
+        }
+
+        *state = TaskState::Cancelled;
+        drop(state);
+
+        self.job.lock().unwrap().take()
+    }
+
+    // Whether the current thread is a worker of this task's pool
+    pub fn on_worker(&self) -> bool {
+        CURRENT_POOL.with(|pool| std::ptr::eq(pool.get(), Arc::as_ptr(&self.pool)))
+    }
+
+    fn run(self: &Arc<Self>) {
+        {
+            let mut state = self.lock();
+            if *state != TaskState::Scheduled {
+                return;
+            }
+            *state = TaskState::Running {
+                again: false,
+                thread: std::thread::current().id(),
+            };
+        }
+
+        if let Some(job) = self.job.lock().unwrap().as_mut() {
+            job();
+        }
+
+        let mut state = self.lock();
+
+        match *state {
+            TaskState::Running { again: true, .. } => {
+                *state = TaskState::Scheduled;
+                drop(state);
+                self.pool.submit(self.clone());
+            }
+            TaskState::Cancelled => {
+                drop(state);
+                drop(self.job.lock().unwrap().take()); // cancelled by the job itself
+                return;
+            }
+            _ => *state = TaskState::Idle,
+        }
+
+        self.condvar.notify_all();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TaskState> {
+        self.state.lock().unwrap()
+    }
+}
+
+struct PoolHandle {
+    shared: Arc<PoolShared>,
+    join_handles: Vec<JoinHandle<()>>,
+}
+
+impl Drop for PoolHandle {
+    // From a worker, that worker stops once its task returns
+    fn drop(&mut self) {
+        self.shared.stop();
+        self.join_handles
+            .drain(..)
+            .filter(|handle| handle.thread().id() != std::thread::current().id())
+            .for_each(|handle| handle.join().unwrap());
+    }
+}
+
+struct PoolState {
+    ready: VecDeque<Arc<PoolTask>>,
+    stopped: bool,
+}
+
+struct PoolShared {
+    state: Mutex<PoolState>,
+    condvar: Condvar,
+}
+
+impl PoolShared {
+    fn submit(&self, task: Arc<PoolTask>) {
+        let mut state = self.state.lock().unwrap();
+        state.ready.push_back(task);
+        self.condvar.notify_one();
+    }
+
+    fn stop(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.stopped = true;
+        self.condvar.notify_all();
+    }
+
+    fn work(&self) {
+        CURRENT_POOL.with(|pool| pool.set(self));
+
+        loop {
+            let task = {
+                let mut state = self.state.lock().unwrap();
+                loop {
+                    if state.stopped {
+                        return;
+                    }
+                    if let Some(task) = state.ready.pop_front() {
+                        break task;
+                    }
+                    state = self.condvar.wait(state).unwrap();
+                }
+            };
+
+            task.run();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn test_task_runs_once_per_schedule() {
+        let pool = ThreadPool::new(2);
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_for_job = counter.clone();
+
+        let task = pool.new_task(Box::new(move || {
+            counter_for_job.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        task.schedule();
+        sleep(Duration::from_millis(100));
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+        task.schedule();
+        assert!(task.cancel().is_some()); // too late to drop the run, or not
+        let counter_after_cancel = counter.load(Ordering::SeqCst);
+
+        task.schedule();
+        sleep(Duration::from_millis(100));
+        assert_eq!(counter.load(Ordering::SeqCst), counter_after_cancel);
+    }
+
+    #[test]
+    fn test_cancel_scheduled_task_from_worker() {
+        let pool = ThreadPool::new(1);
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_for_job = counter.clone();
+
+        let task = pool.new_task(Box::new(move || {
+            counter_for_job.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        // The only worker cancels the task queued behind it instead of waiting for it
+        let task_for_job = task.clone();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let canceller = pool.new_task(Box::new(move || {
+            task_for_job.schedule();
+            sender.send(task_for_job.cancel().is_some()).unwrap();
+        }));
+
+        canceller.schedule();
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).unwrap());
+        canceller.cancel();
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_drop_pool_from_worker() {
+        let pool = ThreadPool::new(2);
+        let pool_for_job = Arc::new(Mutex::new(Some(pool.clone())));
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let task = pool.new_task(Box::new(move || {
+            drop(pool_for_job.lock().unwrap().take()); // the last handle
+            sender.send(()).unwrap();
+        }));
+        drop(pool);
+
+        task.schedule();
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    fn test_task_never_runs_concurrently() {
+        let pool = ThreadPool::new(4);
+        let running = Arc::new(AtomicUsize::new(0));
+        let runs = Arc::new(AtomicUsize::new(0));
+        let (running_for_job, runs_for_job) = (running.clone(), runs.clone());
+
+        let task = pool.new_task(Box::new(move || {
+            assert_eq!(running_for_job.fetch_add(1, Ordering::SeqCst), 0);
+            sleep(Duration::from_millis(10));
+            running_for_job.fetch_sub(1, Ordering::SeqCst);
+            runs_for_job.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        for _ in 0..20 {
+            task.schedule();
+            sleep(Duration::from_millis(2));
+        }
+
+        task.cancel();
+        assert!(runs.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[test]
+    fn test_drop_pool() {
+        let pool = ThreadPool::new(3);
+        assert_eq!(pool.workers(), 3);
+        let clone = pool.clone();
+        drop(pool);
+        drop(clone); // joins the workers
+    }
+}
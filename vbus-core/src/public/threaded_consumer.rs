@@ -1,61 +1,289 @@
-use crate::Payload;
 use crate::private::Consumer;
-use crate::private::queue::Waker;
+use crate::private::queue::{Hook, Queue, Waker};
+use crate::private::supervisor::{Panics, Supervisor};
+use crate::public::thread_pool::PoolTask;
 use crate::tools::atomic_flag::*;
+use crate::{Channel, Payload, ShutdownMode, Supervision, ThreadPool};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread::{JoinHandle, spawn};
+use std::time::{Duration, Instant};
 
 pub struct ThreadedConsumer<T: Payload> {
+    channel: Channel<T>,
+    queue: Queue<T>,
     waker: Waker<T>,
     stopper: AtomicFlagWriter,
-    thread_join_handle: Option<JoinHandle<()>>, // Option -> we can own the handle in drop()
+    drainer: AtomicFlagWriter,
+    finished: Arc<Finished>,
+    runner: Runner,
+    panics: Arc<Panics>,
+    on_drop: ShutdownMode,
+    stopped: bool,
+}
+
+enum Runner {
+    Thread(Option<JoinHandle<()>>), // Option -> we can own the handle in drop()
+    Pool {
+        task: Arc<PoolTask>,
+        hook: Hook,
+        _pool: ThreadPool, // workers must outlive us
+    },
 }
 
 impl<T: Payload> ThreadedConsumer<T> {
     pub(crate) fn new(
         consumer: Consumer<T>,
-        mut process: impl FnMut(Vec<crate::Message<T>>) + Send + 'static,
+        process: impl FnMut(Vec<crate::Message<T>>) + Send + 'static,
+    ) -> Self {
+        Self::new_on(
+            consumer,
+            Supervisor::new(Supervision::default(), process),
+            None,
+            ShutdownMode::Immediate,
+        )
+    }
+
+    pub(crate) fn new_on(
+        consumer: Consumer<T>,
+        supervisor: Supervisor<T>,
+        pool: Option<&ThreadPool>,
+        on_drop: ShutdownMode,
     ) -> Self {
-        let (flag_reader, flag_writer) = atomic_flag();
+        let (stop_reader, stopper) = atomic_flag();
+        let (drain_reader, drainer) = atomic_flag();
+        let finished = Arc::new(Finished::default());
 
+        let channel = consumer.channel().clone();
+        let queue = consumer.queue().clone();
+        queue.manual_ack();
         let waker = consumer.wait_pull_waker();
+        let panics = supervisor.panics();
 
-        let thread_join_handle = spawn(move || {
-            loop {
-                let messages = consumer.wait_pull();
+        let worker = Worker {
+            consumer,
+            supervisor,
+            stop: stop_reader,
+            drain: drain_reader,
+            finished: finished.clone(),
+        };
 
-                if flag_reader.check() {
-                    return;
-                }
+        let runner = match pool {
+            Some(pool) => Self::run_pooled(worker, pool),
+            None => Runner::Thread(Some(spawn(move || worker.run_threaded()))),
+        };
 
-                process(messages);
+        Self {
+            channel,
+            queue,
+            waker,
+            stopper,
+            drainer,
+            finished,
+            runner,
+            panics,
+            on_drop,
+            stopped: false,
+        }
+    }
 
-                if flag_reader.check() {
-                    return;
-                }
+    fn run_pooled(mut worker: Worker<T>, pool: &ThreadPool) -> Runner {
+        let queue = worker.consumer.queue().clone();
+
+        let task = pool.new_task(Box::new(move || worker.run_once()));
+
+        let task_for_hook = task.clone();
+        let hook: Hook = Arc::new(move || task_for_hook.schedule());
+        queue.add_hook(&hook);
+        task.schedule(); // the queue may have been prefilled
+
+        Runner::Pool {
+            task,
+            hook,
+            _pool: pool.clone(),
+        }
+    }
+
+    /// Number of messages discarded by the queue overflow policy.
+    pub fn dropped(&self) -> usize {
+        self.queue.dropped()
+    }
+
+    /// Number of times the `process` closure panicked.
+    pub fn panic_count(&self) -> usize {
+        self.panics.count()
+    }
+
+    /// Message of the last panic of the `process` closure, if any.
+    pub fn last_panic(&self) -> Option<String> {
+        self.panics.last()
+    }
+
+    /// Stops the consumer and returns the number of queued messages that were not processed.
+    ///
+    /// With [`ShutdownMode::Drain`], messages pushed from now on are ignored, and the ones already
+    /// queued are processed first, unless `timeout` expires. Either way, the batch being processed
+    /// is always completed.
+    pub fn shutdown(mut self, mode: ShutdownMode, timeout: Option<Duration>) -> usize {
+        self.stop(mode, timeout)
+    }
+
+    pub(crate) fn stop(&mut self, mode: ShutdownMode, timeout: Option<Duration>) -> usize {
+        if self.stopped {
+            return 0;
+        }
+        self.stopped = true;
+
+        // From a worker of our pool, our task may be queued behind the caller: drain it here
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let on_worker = matches!(&self.runner, Runner::Pool { task, .. } if task.on_worker());
+        let drain_here = mode == ShutdownMode::Drain && on_worker;
+
+        if mode == ShutdownMode::Drain {
+            self.queue.close(); // freezes its content
+            self.channel.remove_queue(&self.queue);
+            self.drainer.raise();
+            self.waker.wake_up();
+            if !drain_here {
+                self.finished.wait(deadline);
             }
-        });
+        }
 
-        Self {
-            waker,
-            stopper: flag_writer,
-            thread_join_handle: Some(thread_join_handle),
+        if !drain_here {
+            self.stopper.raise();
         }
+
+        match &mut self.runner {
+            Runner::Thread(thread_join_handle) => {
+                self.waker.wake_up();
+                thread_join_handle.take().unwrap().join().unwrap();
+            }
+            Runner::Pool { task, hook, .. } => {
+                self.queue.remove_hook(hook);
+                let job = task.cancel(); // dropping it releases the consumer
+
+                if let Some(mut job) = job.filter(|_| drain_here) {
+                    while !self.finished.is_set() && deadline.is_none_or(|d| Instant::now() < d) {
+                        job();
+                    }
+                }
+            }
+        }
+
+        self.queue.len() // the consumer has been dropped, which closed the queue
     }
 }
 
 impl<T: Payload> Drop for ThreadedConsumer<T> {
     fn drop(&mut self) {
-        self.stopper.raise();
-        self.waker.wake_up();
-        self.thread_join_handle.take().unwrap().join().unwrap();
+        self.stop(self.on_drop, None);
+
+        if let Some(payload) = self.panics.take_payload()
+            && !std::thread::panicking()
+        {
+            std::panic::resume_unwind(payload);
+        }
+    }
+}
+
+// Consumer side, moved to the thread or pool task
+struct Worker<T: Payload> {
+    consumer: Consumer<T>,
+    supervisor: Supervisor<T>,
+    stop: AtomicFlagReader,
+    drain: AtomicFlagReader,
+    finished: Arc<Finished>,
+}
+
+impl<T: Payload> Worker<T> {
+    fn run_threaded(mut self) {
+        loop {
+            let draining = self.drain.check();
+
+            let messages = if draining {
+                self.consumer.pull()
+            } else {
+                self.consumer.wait_pull()
+            };
+
+            if self.stop.check() || (draining && messages.is_empty()) {
+                break;
+            }
+
+            if !self.process(messages) || self.stop.check() {
+                break;
+            }
+        }
+
+        self.finished.signal();
+    }
+
+    // One batch per run, so that consumers sharing a pool take turns
+    fn run_once(&mut self) {
+        if self.finished.is_set() || self.stop.check() {
+            return;
+        }
+
+        let messages = self.consumer.pull();
+        if !messages.is_empty() && !self.process(messages) {
+            self.consumer.queue().close(); // don't keep flushes waiting for us
+            self.finished.signal();
+            return;
+        }
+
+        if self.drain.check() && self.consumer.queue().len() == 0 {
+            self.finished.signal();
+        }
+    }
+
+    // Acknowledged once processed, for Channel::flush()
+    fn process(&mut self, messages: Vec<crate::Message<T>>) -> bool {
+        let count = messages.len();
+        let result = self.supervisor.process(messages);
+        self.consumer.queue().ack(count);
+        result
+    }
+}
+
+#[derive(Default)]
+struct Finished {
+    done: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl Finished {
+    fn signal(&self) {
+        *self.done.lock().unwrap() = true;
+        self.condvar.notify_all();
+    }
+
+    fn is_set(&self) -> bool {
+        *self.done.lock().unwrap()
+    }
+
+    fn wait(&self, deadline: Option<Instant>) {
+        let mut done = self.done.lock().unwrap();
+
+        while !*done {
+            done = match deadline {
+                None => self.condvar.wait(done).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return;
+                    }
+                    self.condvar.wait_timeout(done, deadline - now).unwrap().0
+                }
+            };
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::ThreadedConsumer;
-    use crate::Channel;
     use crate::private::test_tools::TestPayload;
+    use crate::{Channel, ConsumerConfig, OverflowPolicy, ShutdownMode, Supervision, ThreadPool};
+    use std::panic::{AssertUnwindSafe, catch_unwind};
     use std::sync::{Arc, Mutex};
     use std::thread::sleep;
     use std::time::Duration;
@@ -78,11 +306,295 @@ mod tests {
             .iter()
             .for_each(|x| channel.push(TestPayload::new(*x)));
 
-        sleep(Duration::from_millis(500));
+        channel.flush();
+        assert_eq!(processed.lock().unwrap().len(), reference.len());
         drop(tc);
-        sleep(Duration::from_millis(200));
 
-        assert_eq!(processed.lock().unwrap().len(), reference.len());
         assert_eq!(processed.lock().unwrap().as_slice(), reference);
     }
+
+    #[test]
+    fn test_bounded_threaded_consumer() {
+        let channel = Channel::<TestPayload>::new();
+        let processed = Arc::new(Mutex::new(Vec::<usize>::new()));
+        let processed_for_thread = processed.clone();
+        let (started, started_receiver) = std::sync::mpsc::channel();
+
+        let tc =
+            channel.new_bounded_threaded_consumer(2, OverflowPolicy::DropNewest, move |input| {
+                let _ = started.send(());
+                sleep(Duration::from_millis(100)); // slow consumer
+                let mut output = processed_for_thread.lock().unwrap();
+                input.iter().for_each(|message| {
+                    output.push(message.get_payload().value());
+                });
+            });
+
+        channel.push(TestPayload::new(0));
+        started_receiver.recv().unwrap(); // consumer is now busy with the first message
+        (1..10).for_each(|x| channel.push(TestPayload::new(x)));
+
+        channel.flush();
+        assert_eq!(tc.dropped(), 7);
+        drop(tc);
+
+        assert_eq!(processed.lock().unwrap().as_slice(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_pooled_consumers() {
+        const CONSUMERS: usize = 16;
+        const MESSAGES: usize = 1000;
+
+        let pool = ThreadPool::new(3);
+        let channel = Channel::<TestPayload>::new();
+        let config = ConsumerConfig::new().pool(&pool);
+
+        let outputs: Vec<_> = (0..CONSUMERS)
+            .map(|_| Arc::new(Mutex::new(Vec::<usize>::new())))
+            .collect();
+        let consumers: Vec<_> = outputs
+            .iter()
+            .map(|output| {
+                let output = output.clone();
+                channel.new_threaded_consumer_with(&config, move |input| {
+                    let mut output = output.lock().unwrap();
+                    input
+                        .iter()
+                        .for_each(|m| output.push(m.get_payload().value()));
+                })
+            })
+            .collect();
+        assert_eq!(channel.queues_len(), CONSUMERS);
+
+        (0..MESSAGES).for_each(|x| channel.push(TestPayload::new(x)));
+
+        channel.flush();
+        drop(consumers);
+        assert_eq!(channel.queues_len(), 0);
+
+        let expected: Vec<usize> = (0..MESSAGES).collect();
+        for output in outputs {
+            assert_eq!(output.lock().unwrap().as_slice(), expected);
+        }
+    }
+
+    #[test]
+    fn test_pooled_consumer_drop_while_busy() {
+        let pool = ThreadPool::new(1);
+        let channel = Channel::<TestPayload>::new();
+        let processed = Arc::new(Mutex::new(0usize));
+        let processed_for_pool = processed.clone();
+
+        let tc =
+            channel.new_threaded_consumer_with(&ConsumerConfig::new().pool(&pool), move |input| {
+                sleep(Duration::from_millis(200));
+                *processed_for_pool.lock().unwrap() += input.len();
+            });
+
+        channel.push(TestPayload::new(0));
+        sleep(Duration::from_millis(50)); // first batch is being processed
+        channel.push(TestPayload::new(1));
+
+        drop(tc); // waits for the running batch, skips the next one
+        drop(pool);
+
+        assert_eq!(*processed.lock().unwrap(), 1);
+    }
+
+    fn panic_on_odd(input: Vec<crate::Message<TestPayload>>) {
+        if input.iter().any(|m| m.get_payload().value() % 2 == 1) {
+            panic!("odd value");
+        }
+    }
+
+    #[test]
+    fn test_drop_from_pool_task() {
+        let pool = ThreadPool::new(1);
+        let config = ConsumerConfig::new().pool(&pool);
+        let processed = Arc::new(Mutex::new(Vec::<usize>::new()));
+
+        for mode in [ShutdownMode::Immediate, ShutdownMode::Drain] {
+            let channel = Channel::<TestPayload>::new();
+            let other = Arc::new(Mutex::new(Some(slow_consumer(
+                &channel,
+                &config.clone().on_drop(mode),
+                &processed,
+            ))));
+
+            // Drops the other consumer, likely queued behind this one on the only worker
+            let other_for_pool = other.clone();
+            let dropper = channel.new_threaded_consumer_with(&config, move |_| {
+                drop(other_for_pool.lock().unwrap().take());
+            });
+
+            channel.push(TestPayload::new(0));
+            channel.flush();
+            assert!(other.lock().unwrap().is_none());
+            drop(dropper);
+        }
+
+        // Drained: processed in the dropper's task
+        assert_eq!(processed.lock().unwrap().last(), Some(&0));
+    }
+
+    #[test]
+    fn test_log_and_skip() {
+        let pool = ThreadPool::new(1);
+
+        for config in [ConsumerConfig::new(), ConsumerConfig::new().pool(&pool)] {
+            let channel = Channel::<TestPayload>::new();
+            let tc = channel.new_threaded_consumer_with(&config, panic_on_odd); // the default
+
+            for i in 0..4 {
+                channel.push(TestPayload::new(i));
+                channel.flush(); // one message per batch
+            }
+
+            assert_eq!(tc.panic_count(), 2);
+            assert_eq!(tc.last_panic().unwrap(), "odd value");
+            drop(tc); // must not panic
+        }
+    }
+
+    #[test]
+    fn test_propagate() {
+        let pool = ThreadPool::new(1);
+
+        for config in [ConsumerConfig::new(), ConsumerConfig::new().pool(&pool)] {
+            let channel = Channel::<TestPayload>::new();
+            let config = config.supervision(Supervision::Propagate);
+            let tc = channel.new_threaded_consumer_with(&config, panic_on_odd);
+
+            channel.push(TestPayload::new(1));
+            channel.flush();
+            assert_eq!(tc.panic_count(), 1);
+
+            let result = catch_unwind(AssertUnwindSafe(|| drop(tc)));
+            let payload = result.unwrap_err();
+            assert_eq!(*payload.downcast_ref::<&str>().unwrap(), "odd value");
+        }
+    }
+
+    #[test]
+    fn test_restart() {
+        let channel = Channel::<TestPayload>::new();
+        let processed = Arc::new(Mutex::new(Vec::<usize>::new()));
+        let processed_for_factory = processed.clone();
+
+        let config = ConsumerConfig::new().supervision(Supervision::Restart);
+        let tc = channel.new_supervised_consumer(&config, move || {
+            let processed = processed_for_factory.clone();
+            let mut count = 0;
+            move |input: Vec<crate::Message<TestPayload>>| {
+                count += input.len();
+                if count > 2 {
+                    panic!("too many");
+                }
+                let mut processed = processed.lock().unwrap();
+                input
+                    .iter()
+                    .for_each(|m| processed.push(m.get_payload().value()));
+            }
+        });
+
+        for i in 0..6 {
+            channel.push(TestPayload::new(i));
+            channel.flush(); // one message per batch
+        }
+
+        assert_eq!(tc.panic_count(), 2);
+        drop(tc);
+
+        // Every third message makes the closure panic, then a fresh one takes over
+        assert_eq!(processed.lock().unwrap().as_slice(), [0, 1, 3, 4]);
+    }
+
+    fn slow_consumer(
+        channel: &Channel<TestPayload>,
+        config: &ConsumerConfig,
+        processed: &Arc<Mutex<Vec<usize>>>,
+    ) -> ThreadedConsumer<TestPayload> {
+        let processed = processed.clone();
+        channel.new_threaded_consumer_with(config, move |input| {
+            sleep(Duration::from_millis(20));
+            let mut processed = processed.lock().unwrap();
+            input
+                .iter()
+                .for_each(|m| processed.push(m.get_payload().value()));
+        })
+    }
+
+    #[test]
+    fn test_shutdown_drain() {
+        let pool = ThreadPool::new(1);
+
+        for config in [ConsumerConfig::new(), ConsumerConfig::new().pool(&pool)] {
+            let channel = Channel::<TestPayload>::new();
+            let processed = Arc::new(Mutex::new(Vec::<usize>::new()));
+            let tc = slow_consumer(&channel, &config, &processed);
+
+            (0..100).for_each(|x| channel.push(TestPayload::new(x)));
+
+            assert_eq!(tc.shutdown(ShutdownMode::Drain, None), 0);
+            assert_eq!(channel.queues_len(), 0);
+            assert_eq!(
+                processed.lock().unwrap().as_slice(),
+                (0..100).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_shutdown_drain_timeout() {
+        let channel = Channel::<TestPayload>::new();
+        let processed = Arc::new(Mutex::new(Vec::<usize>::new()));
+        let processed_for_thread = processed.clone();
+
+        // One message per batch, 50 ms each
+        let config = ConsumerConfig::new().bounded(1, OverflowPolicy::Block);
+        let tc = channel.new_threaded_consumer_with(&config, move |input| {
+            sleep(Duration::from_millis(50));
+            processed_for_thread
+                .lock()
+                .unwrap()
+                .extend(input.iter().map(|m| m.get_payload().value()));
+        });
+
+        channel.push(TestPayload::new(0));
+        channel.push(TestPayload::new(1));
+
+        let dropped = tc.shutdown(ShutdownMode::Drain, Some(Duration::from_millis(10)));
+        let processed = processed.lock().unwrap().len();
+        assert_eq!(processed + dropped, 2);
+        assert!(dropped <= 1);
+    }
+
+    #[test]
+    fn test_shutdown_immediate() {
+        let channel = Channel::<TestPayload>::new();
+        let processed = Arc::new(Mutex::new(Vec::<usize>::new()));
+        let tc = slow_consumer(&channel, &ConsumerConfig::new(), &processed);
+
+        channel.push(TestPayload::new(0));
+        sleep(Duration::from_millis(10)); // first batch is being processed
+        (1..100).for_each(|x| channel.push(TestPayload::new(x)));
+
+        let dropped = tc.shutdown(ShutdownMode::Immediate, None);
+        assert_eq!(processed.lock().unwrap().as_slice(), [0]);
+        assert_eq!(dropped, 99);
+    }
+
+    #[test]
+    fn test_drain_on_drop() {
+        let channel = Channel::<TestPayload>::new();
+        let processed = Arc::new(Mutex::new(Vec::<usize>::new()));
+        let config = ConsumerConfig::new().on_drop(ShutdownMode::Drain);
+        let tc = slow_consumer(&channel, &config, &processed);
+
+        (0..10).for_each(|x| channel.push(TestPayload::new(x)));
+        drop(tc);
+
+        assert_eq!(processed.lock().unwrap().len(), 10);
+    }
 }
@@ -48,10 +48,10 @@ mod tests {
     #[test]
     fn test_raise() {
         let (reader, mut writer) = atomic_flag();
-        assert_eq!(reader.check(), false);
+        assert!(!reader.check());
 
         writer.raise();
-        assert_eq!(reader.check(), true);
+        assert!(reader.check());
     }
 
     #[test]
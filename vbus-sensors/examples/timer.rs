@@ -0,0 +1,16 @@
+use std::time::Duration;
+use vbus_core::Channel;
+use vbus_sensors::timer::*;
+
+fn main() {
+    let channel = Channel::<Tick>::new();
+    let mut subscription = channel.subscribe();
+    let _timer = Timer::new(&channel, Duration::from_millis(100), TimerMode::DriftFree);
+
+    println!("This program prints 20 ticks of a 10 Hz timer.");
+
+    for message in subscription.by_ref().take(20) {
+        let tick = message.get_payload();
+        println!("tick {} ({:?} late)", tick.sequence, tick.lateness);
+    }
+}
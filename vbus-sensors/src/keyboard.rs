@@ -1,10 +1,11 @@
 use selecting::Selector;
 use std::io::Read;
+use std::ops::ControlFlow;
 use std::os::fd::{AsRawFd, RawFd};
 use std::thread::{JoinHandle, spawn};
 use termios::*;
 use vbus_core::tools::pipe_flag::*;
-use vbus_core::{Channel, Payload};
+use vbus_core::{Channel, Payload, Reactor, SourceId, SourceRemover};
 
 #[derive(bincode::Encode, bincode::Decode, Debug)]
 pub struct KeyboardData {
@@ -15,19 +16,23 @@ impl Payload for KeyboardData {}
 
 pub struct Keyboard {
     termios_backup: Termios,
-    flag_writer: PipeFlagWriter,
-    join_handle: Option<JoinHandle<()>>,
+    runner: Runner,
+}
+
+enum Runner {
+    Thread {
+        flag_writer: PipeFlagWriter,
+        join_handle: Option<JoinHandle<()>>, // Option -> we can own the handle in drop()
+    },
+    Hosted {
+        remover: SourceRemover,
+        id: SourceId,
+    },
 }
 
 impl Keyboard {
     pub fn new(channel: &Channel<KeyboardData>) -> Self {
-        let old_termios = Termios::from_fd(stdin_raw_fd()).unwrap();
-        let mut termios = old_termios; // copy
-
-        termios.c_lflag &= !(ICANON | ECHO);
-        termios.c_cc[VTIME] = 0;
-        termios.c_cc[VMIN] = 1;
-        termios_set(&termios);
+        let old_termios = termios_setup();
 
         let (flag_reader, flag_writer) = pipe_flag();
 
@@ -51,34 +56,82 @@ impl Keyboard {
                     continue;
                 }
 
-                let mut buffer = [0u8; 1];
-                let result = std::io::stdin().read(&mut buffer).unwrap();
-                if result != 1 {
-                    continue;
-                }
-
-                channel_for_thread.push(KeyboardData {
-                    data: buffer[0] as char,
-                });
+                read_key(&channel_for_thread);
             }
         });
 
         Keyboard {
             termios_backup: old_termios,
-            flag_writer,
-            join_handle: Some(thread_join_handle),
+            runner: Runner::Thread {
+                flag_writer,
+                join_handle: Some(thread_join_handle),
+            },
+        }
+    }
+
+    /// Like [`Keyboard::new`], but stdin is read by `reactor` rather than by a dedicated thread.
+    /// Dropping the keyboard removes it from the reactor.
+    pub fn new_hosted(channel: &Channel<KeyboardData>, reactor: &mut Reactor) -> Self {
+        let old_termios = termios_setup();
+        let channel_for_reactor = channel.clone();
+
+        let id = reactor.add_fd(stdin_raw_fd(), move || {
+            read_key(&channel_for_reactor);
+            ControlFlow::Continue(())
+        });
+
+        Keyboard {
+            termios_backup: old_termios,
+            runner: Runner::Hosted {
+                remover: reactor.remover(),
+                id,
+            },
         }
     }
 }
 
 impl Drop for Keyboard {
     fn drop(&mut self) {
-        self.flag_writer.raise();
-        self.join_handle.take().unwrap().join().unwrap();
+        match &mut self.runner {
+            Runner::Thread {
+                flag_writer,
+                join_handle,
+            } => {
+                flag_writer.raise();
+                join_handle.take().unwrap().join().unwrap();
+            }
+            Runner::Hosted { remover, id } => remover.remove(*id),
+        }
+
         termios_set(&self.termios_backup);
     }
 }
 
+fn read_key(channel: &Channel<KeyboardData>) {
+    let mut buffer = [0u8; 1];
+    let result = std::io::stdin().read(&mut buffer).unwrap();
+    if result != 1 {
+        return;
+    }
+
+    channel.push(KeyboardData {
+        data: buffer[0] as char,
+    });
+}
+
+// Returns the previous settings
+fn termios_setup() -> Termios {
+    let old_termios = Termios::from_fd(stdin_raw_fd()).unwrap();
+    let mut termios = old_termios; // copy
+
+    termios.c_lflag &= !(ICANON | ECHO);
+    termios.c_cc[VTIME] = 0;
+    termios.c_cc[VMIN] = 1;
+    termios_set(&termios);
+
+    old_termios
+}
+
 fn termios_set(termios: &Termios) {
     tcsetattr(stdin_raw_fd(), TCSANOW, termios).unwrap();
 }
@@ -1,3 +1,4 @@
 pub mod camera;
 pub mod joystick;
 pub mod keyboard;
+pub mod timer;
@@ -0,0 +1,213 @@
+use selecting::Selector;
+use std::os::fd::AsRawFd;
+use std::thread::{JoinHandle, spawn};
+use std::time::{Duration, Instant};
+use vbus_core::tools::pipe_flag::*;
+use vbus_core::{Channel, Payload};
+
+#[derive(bincode::Encode, bincode::Decode, Debug)]
+pub struct Tick {
+    /// Index of the period since the timer started, from 0.
+    pub sequence: u64,
+    /// How long after its scheduled time the tick was pushed.
+    pub lateness: Duration,
+}
+
+impl Payload for Tick {}
+
+/// What a [`Timer`] does when ticks are late, e.g. because the machine is overloaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerMode {
+    /// Ticks stay on the `start + n * period` schedule: missed ticks are pushed in a burst.
+    DriftFree,
+    /// Missed ticks are not pushed, which shows as gaps in the sequence numbers.
+    SkipMissed,
+}
+
+/// Pushes a [`Tick`] to a channel every period, starting one period from now.
+pub struct Timer {
+    flag_writer: PipeFlagWriter,
+    join_handle: Option<JoinHandle<()>>, // Option -> we can own the handle in drop()
+}
+
+impl Timer {
+    /// Panics if `period` is zero.
+    pub fn new(channel: &Channel<Tick>, period: Duration, mode: TimerMode) -> Self {
+        assert!(!period.is_zero(), "timer period must not be zero");
+
+        let (flag_reader, flag_writer) = pipe_flag();
+        let channel_for_thread = channel.clone();
+
+        let thread_join_handle = spawn(move || {
+            let mut selector = Selector::new();
+            selector.add_read(&flag_reader.as_raw_fd());
+
+            let start = Instant::now();
+            let mut sequence = 0u64;
+
+            loop {
+                let Some(scheduled) = scheduled_time(start, period, sequence) else {
+                    return; // beyond what Instant can represent
+                };
+
+                let timeout = scheduled.saturating_duration_since(Instant::now());
+                let result = selector.select_timeout(timeout).unwrap();
+
+                if result.is_read(&flag_reader.as_raw_fd()) {
+                    return;
+                }
+
+                let now = Instant::now();
+                if now < scheduled {
+                    continue; // early wake up
+                }
+
+                channel_for_thread.push(Tick {
+                    sequence,
+                    lateness: now - scheduled,
+                });
+
+                sequence = next_sequence(mode, sequence, now - scheduled, period);
+            }
+        });
+
+        Timer {
+            flag_writer,
+            join_handle: Some(thread_join_handle),
+        }
+    }
+}
+
+// Start of period `sequence` + 1, computed from the start so that errors don't accumulate
+fn scheduled_time(start: Instant, period: Duration, sequence: u64) -> Option<Instant> {
+    let nanos = period.as_nanos().checked_mul(sequence as u128 + 1)?;
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    start.checked_add(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+fn next_sequence(mode: TimerMode, sequence: u64, lateness: Duration, period: Duration) -> u64 {
+    let skipped = match mode {
+        TimerMode::DriftFree => 0,
+        TimerMode::SkipMissed => {
+            u64::try_from(lateness.as_nanos() / period.as_nanos()).unwrap_or(u64::MAX)
+        }
+    };
+
+    sequence.saturating_add(skipped).saturating_add(1)
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.flag_writer.raise();
+        self.join_handle.take().unwrap().join().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+    use vbus_core::{OverflowPolicy, Subscription};
+
+    const PERIOD: Duration = Duration::from_millis(10);
+
+    // Blocks the timer on a full queue for `stall`, then returns the sequence numbers and
+    // lateness of the ticks that follow
+    fn stalled_ticks(mode: TimerMode, stall: Duration) -> Vec<(u64, Duration)> {
+        let channel = Channel::<Tick>::new();
+        let mut subscription = channel.subscribe_bounded(1, OverflowPolicy::Block);
+        let timer = Timer::new(&channel, PERIOD, mode);
+
+        sleep(stall);
+        let mut ticks = Vec::new();
+        while ticks.len() < 30 {
+            ticks.extend(
+                subscription
+                    .wait_pull()
+                    .iter()
+                    .map(|m| (m.get_payload().sequence, m.get_payload().lateness)),
+            );
+        }
+
+        drop(subscription); // releases the timer thread if it is blocked
+        drop(timer);
+        ticks
+    }
+
+    fn sequences(ticks: &[(u64, Duration)]) -> Vec<u64> {
+        ticks.iter().map(|&(sequence, _)| sequence).collect()
+    }
+
+    #[test]
+    fn test_drift_free() {
+        let ticks = stalled_ticks(TimerMode::DriftFree, PERIOD * 20);
+
+        // Missed ticks are pushed in a burst, without gaps
+        let expected: Vec<u64> = (0..ticks.len() as u64).collect();
+        assert_eq!(sequences(&ticks), expected);
+        assert!(ticks.iter().any(|&(_, lateness)| lateness >= PERIOD * 10));
+    }
+
+    #[test]
+    fn test_skip_missed() {
+        let ticks = stalled_ticks(TimerMode::SkipMissed, PERIOD * 20);
+        let sequences = sequences(&ticks);
+
+        // One gap, right after the tick that was blocked
+        let gaps: Vec<usize> = (1..sequences.len())
+            .filter(|&i| sequences[i] != sequences[i - 1] + 1)
+            .collect();
+        assert_eq!(gaps.len(), 1, "{sequences:?}");
+        assert!(gaps[0] <= 3 && sequences[gaps[0]] >= 10, "{sequences:?}");
+
+        // Once the stall is over, ticks are on time again
+        let late = ticks[gaps[0]..]
+            .iter()
+            .filter(|&&(_, lateness)| lateness >= PERIOD * 5);
+        assert_eq!(late.count(), 0);
+    }
+
+    #[test]
+    fn test_drop() {
+        let channel = Channel::<Tick>::new();
+        let mut subscription: Subscription<Tick> = channel.subscribe();
+        let timer = Timer::new(&channel, PERIOD, TimerMode::DriftFree);
+
+        assert!(
+            !subscription
+                .wait_pull_timeout(Duration::from_secs(5))
+                .is_empty()
+        );
+        drop(timer); // stops the thread
+
+        subscription.try_pull();
+        sleep(PERIOD * 5);
+        assert!(subscription.try_pull().is_empty());
+    }
+
+    #[test]
+    fn test_schedule_overflow() {
+        let start = Instant::now();
+        assert_eq!(scheduled_time(start, PERIOD, 0), Some(start + PERIOD));
+        assert_eq!(
+            scheduled_time(start, PERIOD, 99),
+            Some(start + PERIOD * 100)
+        );
+        assert_eq!(scheduled_time(start, Duration::MAX, 1), None);
+        assert_eq!(
+            scheduled_time(start, Duration::from_secs(1 << 40), u64::MAX),
+            None
+        );
+
+        let lateness = Duration::MAX;
+        let period = Duration::from_nanos(1);
+        assert_eq!(
+            next_sequence(TimerMode::SkipMissed, 5, lateness, period),
+            u64::MAX
+        );
+        assert_eq!(
+            next_sequence(TimerMode::DriftFree, u64::MAX, lateness, period),
+            u64::MAX
+        );
+    }
+}